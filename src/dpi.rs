@@ -0,0 +1,31 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types for working in physical and logical screen space.
+
+/// A position represented in physical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PhysicalPosition<P> {
+    pub x: P,
+    pub y: P,
+}
+
+impl<P> PhysicalPosition<P> {
+    pub const fn new(x: P, y: P) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A size represented in physical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PhysicalSize<P> {
+    pub width: P,
+    pub height: P,
+}
+
+impl<P> PhysicalSize<P> {
+    pub const fn new(width: P, height: P) -> Self {
+        Self { width, height }
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The [`Event`] type and the other types delivered alongside it to [`crate::event_loop::EventLoop::run`].
+
+use crate::{
+    dpi::PhysicalPosition,
+    menu::{MenuId, MenuType},
+    system_tray::Rectangle,
+    TrayId,
+};
+
+/// Describes why [`Event::NewEvents`] was just emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartCause {
+    /// The event loop has just started running for the first time.
+    Init,
+    /// A [`crate::event_loop::ControlFlow::WaitUntil`] deadline was reached.
+    ResumeTimeReached,
+    /// Emitted on every iteration of the event loop while [`crate::event_loop::ControlFlow::Poll`]
+    /// is set.
+    Poll,
+}
+
+/// An event produced by the platform and delivered to the closure passed to
+/// [`crate::event_loop::EventLoop::run`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a, T: 'static> {
+    /// Emitted once when the event loop starts and, while `WaitUntil` or `Poll` control flow is
+    /// set, at the start of each iteration before that iteration's other events. Iterations under
+    /// [`crate::event_loop::ControlFlow::Wait`] emit no `NewEvents`.
+    NewEvents(StartCause),
+    /// A custom menu item (from a window menu bar or a tray's [`crate::menu::ContextMenu`]) was
+    /// activated.
+    MenuEvent {
+        menu_id: MenuId,
+        origin: MenuType,
+    },
+    /// A tray icon was interacted with.
+    TrayEvent {
+        id: TrayId,
+        event: TrayEvent,
+    },
+    /// A user event created via [`crate::event_loop::EventLoop::with_user_event`].
+    UserEvent(&'a T),
+}
+
+/// The kind of interaction a tray icon received, delivered via [`Event::TrayEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayEvent {
+    /// The tray icon was left-clicked.
+    LeftClick,
+    /// The tray icon was double-clicked.
+    ///
+    /// Emitted on Windows from `WM_LBUTTONDBLCLK`, on macOS by inspecting
+    /// `NSEvent.clickCount` on the status item's button action, and on Linux from a second
+    /// StatusNotifierItem `Activate` call within a short window of the first (or, with the
+    /// `ayatana` backend, from a GTK `button-press-event` with
+    /// `gdk::EventType::DoubleButtonPress`). A common use is
+    /// restoring/un-minimizing the main window, reserving left-click for a custom toggle and
+    /// right-click for a context menu.
+    DoubleClick { position: PhysicalPosition<f64>, bounds: Rectangle },
+    /// The tray icon was right-clicked.
+    ///
+    /// Emitted on Windows from `WM_RBUTTONUP`, on macOS from `NSEventTypeRightMouseDown`, and on
+    /// Linux from a StatusNotifierItem `SecondaryActivate` call (or, with the `ayatana` backend,
+    /// from a GTK `button-press-event` gated on `event.button() == 3`).
+    RightClick { position: PhysicalPosition<f64>, bounds: Rectangle },
+}
@@ -0,0 +1,117 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The event loop that drives a tao application and the control flow it runs under.
+
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use crate::event::Event;
+
+/// Target that platform-specific extension traits attach their `build`/window-creation methods
+/// to. Kept intentionally opaque; application code only ever sees a reference to it inside the
+/// [`EventLoop::run`] closure.
+pub struct EventLoopWindowTarget<T: 'static> {
+    pub(crate) _marker: PhantomData<T>,
+}
+
+/// The central object that owns the platform event source and dispatches [`Event`]s to the
+/// application closure passed to [`EventLoop::run`].
+pub struct EventLoop<T: 'static = ()> {
+    pub(crate) window_target: EventLoopWindowTarget<T>,
+}
+
+impl EventLoop<()> {
+    /// Builds a new event loop with no user event type.
+    pub fn new() -> Self {
+        Self::with_user_event()
+    }
+}
+
+impl<T: 'static> EventLoop<T> {
+    /// Builds a new event loop carrying a user-defined event type `T`.
+    pub fn with_user_event() -> Self {
+        Self {
+            window_target: EventLoopWindowTarget {
+                _marker: PhantomData,
+            },
+        }
+    }
+
+    /// Hands control of the calling thread to tao's event loop, invoking `event_handler` once
+    /// per dispatched [`Event`] until `control_flow` is set to [`ControlFlow::Exit`].
+    ///
+    /// Never returns; matches the winit/tao convention of taking ownership of `self` and running
+    /// until the process decides to exit.
+    pub fn run<F>(self, mut event_handler: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = ControlFlow::Wait;
+        let target = &self.window_target;
+
+        let mut dispatch = |event, control_flow: &mut ControlFlow| {
+            event_handler(event, target, control_flow);
+        };
+
+        dispatch(Event::NewEvents(crate::event::StartCause::Init), &mut control_flow);
+
+        loop {
+            if control_flow == ControlFlow::Exit {
+                drop(dispatch);
+                drop(event_handler);
+                std::process::exit(0);
+            }
+
+            match control_flow {
+                ControlFlow::WaitUntil(deadline) => {
+                    let now = Instant::now();
+                    if deadline > now {
+                        crate::platform_impl::pump_events_until(deadline);
+                    }
+                    dispatch(
+                        Event::NewEvents(crate::event::StartCause::ResumeTimeReached),
+                        &mut control_flow,
+                    );
+                }
+                ControlFlow::Poll => {
+                    crate::platform_impl::pump_events(false);
+                    dispatch(Event::NewEvents(crate::event::StartCause::Poll), &mut control_flow);
+                }
+                ControlFlow::Wait | ControlFlow::Exit => {
+                    // Platform callbacks (menu activations, tray clicks) run on the real OS
+                    // event source, which this blocks on until one is ready to fire and pushes
+                    // into the internal queue drained below.
+                    crate::platform_impl::pump_events(true);
+                }
+            }
+
+            for internal_event in crate::platform_impl::drain_events() {
+                match internal_event {
+                    crate::platform_impl::InternalEvent::Menu { id, origin } => {
+                        dispatch(Event::MenuEvent { menu_id: id, origin }, &mut control_flow);
+                    }
+                    crate::platform_impl::InternalEvent::Tray { id, event } => {
+                        dispatch(Event::TrayEvent { id, event }, &mut control_flow);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Describes how the event loop should behave once the current iteration has finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Block until a new OS event (or a woken-up timer) arrives.
+    Wait,
+    /// Block until `Instant` is reached, then deliver
+    /// [`crate::event::StartCause::ResumeTimeReached`]. An OS event arriving earlier does not end
+    /// the wait: the events it queues are delivered after `ResumeTimeReached`.
+    WaitUntil(Instant),
+    /// Run the event loop again immediately without waiting for new events.
+    Poll,
+    /// Stop running the event loop, dropping the closure passed to [`EventLoop::run`].
+    Exit,
+}
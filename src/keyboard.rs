@@ -0,0 +1,108 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keyboard modifier and physical-key types, shared by [`crate::menu::Accelerator`] and (in the
+//! future) window input events.
+
+use std::ops::{BitOr, BitOrAssign};
+
+/// A bitset of keyboard modifier keys, e.g. held down alongside a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ModifiersState(u8);
+
+impl ModifiersState {
+    pub const SHIFT: Self = Self(0b0001);
+    pub const CONTROL: Self = Self(0b0010);
+    pub const ALT: Self = Self(0b0100);
+    /// The "Super" key: ⌘ Command on macOS, the Windows key on Windows, Super on Linux.
+    pub const SUPER: Self = Self(0b1000);
+
+    /// Returns an empty modifier set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns whether every modifier set in `other` is also set in `self`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ModifiersState {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ModifiersState {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A physical keyboard key, identified independently of the user's keyboard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum KeyCode {
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Space,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    Delete,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+}
@@ -0,0 +1,43 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! tao is a cross-platform windowing and event loop library, with a focus on the desktop tray
+//! icon / menu facilities that back the `pomodoro` example in this repository.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+pub mod dpi;
+pub mod event;
+pub mod event_loop;
+pub mod keyboard;
+pub mod menu;
+pub mod platform;
+pub mod system_tray;
+pub mod window;
+
+mod platform_impl;
+
+static TRAY_ID_COUNTER: AtomicU16 = AtomicU16::new(1);
+
+/// Identifies a [`system_tray::SystemTray`], so an application owning more than one tray icon can
+/// tell their [`event::Event::TrayEvent`]s apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TrayId(pub(crate) u64);
+
+impl TrayId {
+    /// Creates a `TrayId` from a stable, human-readable name.
+    pub fn new(name: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+
+    pub(crate) fn next() -> Self {
+        Self(TRAY_ID_COUNTER.fetch_add(1, Ordering::Relaxed) as u64)
+    }
+}
@@ -28,13 +28,14 @@ fn main() {
         ActivationPolicy, EventLoopExtMacOS, SystemTrayBuilderExtMacOS, SystemTrayExtMacOS,
     };
 
+    use std::str::FromStr;
     use std::time::{Duration, Instant};
     use tao::{
         event::{Event, StartCause},
         event_loop::{ControlFlow, EventLoop},
-        menu::{ContextMenu as Menu, MenuItemAttributes, MenuType, MenuItem},
+        menu::{Accelerator, ContextMenu as Menu, MenuItemAttributes, MenuType, MenuItem, RadioGroup},
         system_tray::SystemTrayBuilder,
-        TrayId, window::Icon,
+        TrayId,
     };
 
     // Types
@@ -57,9 +58,9 @@ fn main() {
 
     let mut current_status: Status = Status::Idle;
     let mut current_time_left: Duration = Duration::new(0, 0);
+    let mut session_length = Duration::new(20 * 60, 0);
     let one_second = Duration::new(1, 0);
     let zero_seconds = Duration::new(0, 0);
-    let twenty_minutes = Duration::new(20 * 60, 0);
 
     fn format_number(number: u64) -> String {
         if number < 10 {
@@ -89,9 +90,37 @@ fn main() {
     let path = concat!(env!("CARGO_MANIFEST_DIR"), "/icons/timer.png");
     let main_tray_id = TrayId::new("main-tray");
     let icon = load_icon(std::path::Path::new(path));
+
     let mut tray_menu = Menu::new();
-    let menu_item = tray_menu.add_item(MenuItemAttributes::new("Clear"));
-    let quit = tray_menu.add_native_item(MenuItem::Quit).unwrap();
+    let mut menu_item = tray_menu.add_item(MenuItemAttributes::new("Clear"));
+    let notifications_item =
+        tray_menu.add_check_item(MenuItemAttributes::new("Notifications"), true);
+    tray_menu.add_native_item(MenuItem::Separator);
+
+    let duration_group = RadioGroup::new();
+    let twenty_minutes = tray_menu.add_radio_item(
+        MenuItemAttributes::new("20 minutes"),
+        &duration_group,
+        true,
+    );
+    let twenty_five_minutes = tray_menu.add_radio_item(
+        MenuItemAttributes::new("25 minutes"),
+        &duration_group,
+        false,
+    );
+    let thirty_minutes = tray_menu.add_radio_item(
+        MenuItemAttributes::new("30 minutes"),
+        &duration_group,
+        false,
+    );
+    tray_menu.add_native_item(MenuItem::Separator);
+
+    let about_item = tray_menu.add_icon_item(MenuItemAttributes::new("About"), icon.clone());
+    let simplify_item = tray_menu.add_item(MenuItemAttributes::new("Simplify Menu"));
+    let mut quit = tray_menu.add_item(
+        MenuItemAttributes::new("Quit")
+            .with_accelerator(Accelerator::from_str("CmdOrCtrl+Q").unwrap()),
+    );
 
     #[cfg(target_os = "linux")]
     let system_tray = SystemTrayBuilder::new(icon.clone(), Some(tray_menu))
@@ -146,15 +175,37 @@ fn main() {
                     system_tray.take();
                     *control_flow = ControlFlow::Exit;
                 } else if menu_id == menu_item.clone().id() {
-                    #[cfg(target_os = "macos")]
-                    {
-                        if let Some(tray) = system_tray.as_mut() {
-                            current_status = Status::Idle;
-                            current_time_left = zero_seconds;
-                            tray.set_title(&format_timer(current_time_left));
-                            *control_flow = ControlFlow::Wait;
-                        }
+                    current_status = Status::Idle;
+                    current_time_left = zero_seconds;
+                    menu_item.set_enabled(false);
+                    menu_item.set_label("Cleared");
+                    if let Some(tray) = system_tray.as_mut() {
+                        tray.set_title(&format_timer(current_time_left));
+                        *control_flow = ControlFlow::Wait;
+                    }
+                } else if menu_id == simplify_item.clone().id() {
+                    // Replace the full menu with a minimal one, demonstrating
+                    // `SystemTray::set_menu` updating the tray's menu in place.
+                    let mut simplified_menu = Menu::new();
+                    if let Some(simplified_quit) = simplified_menu.add_native_item(MenuItem::Quit) {
+                        quit = simplified_quit;
                     }
+                    if let Some(tray) = system_tray.as_mut() {
+                        tray.set_menu(Some(simplified_menu));
+                    }
+                } else if menu_id == notifications_item.clone().id() {
+                    println!(
+                        "Notifications are now {}",
+                        if notifications_item.is_checked() { "on" } else { "off" }
+                    );
+                } else if menu_id == twenty_minutes.clone().id() {
+                    session_length = Duration::new(20 * 60, 0);
+                } else if menu_id == twenty_five_minutes.clone().id() {
+                    session_length = Duration::new(25 * 60, 0);
+                } else if menu_id == thirty_minutes.clone().id() {
+                    session_length = Duration::new(30 * 60, 0);
+                } else if menu_id == about_item.clone().id() {
+                    println!("totodoro - a tao system tray example");
                 }
             }
             Event::TrayEvent { id, event, .. } => {
@@ -165,7 +216,9 @@ fn main() {
                                 match current_status {
                                     Status::Idle => {
                                         current_status = Status::Running;
-                                        current_time_left = twenty_minutes;
+                                        current_time_left = session_length;
+                                        menu_item.set_enabled(true);
+                                        menu_item.set_label("Clear");
                                         tray.set_title(&format_timer(current_time_left));
                                         *control_flow = control_wait_until(one_second);
                                     }
@@ -181,7 +234,19 @@ fn main() {
                                 }
                             }
                         }
-                        _ => {}
+                        tao::event::TrayEvent::DoubleClick { .. } => {
+                            current_status = Status::Idle;
+                            current_time_left = zero_seconds;
+                            menu_item.set_enabled(false);
+                            menu_item.set_label("Cleared");
+                            if let Some(tray) = system_tray.as_mut() {
+                                tray.set_title(&format_timer(current_time_left));
+                                *control_flow = ControlFlow::Wait;
+                            }
+                        }
+                        tao::event::TrayEvent::RightClick { position, .. } => {
+                            println!("Right-clicked the tray icon at {:?}", position);
+                        }
                     }
                 }
             }
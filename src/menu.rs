@@ -0,0 +1,512 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Native menus: context menus attached to a window or a [`crate::system_tray::SystemTray`].
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    str::FromStr,
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+use crate::keyboard::{KeyCode, ModifiersState};
+
+static MENU_ID_COUNTER: AtomicU16 = AtomicU16::new(1);
+
+/// Identifies a menu item across the lifetime of the application; delivered back on
+/// [`crate::event::Event::MenuEvent`] so application code can match the item that was clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MenuId(pub(crate) u16);
+
+impl MenuId {
+    fn next() -> Self {
+        Self(MENU_ID_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Where a [`crate::event::Event::MenuEvent`] originated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuType {
+    /// The item lives in a tray icon's context menu.
+    ContextMenu,
+    /// The item lives in a window's menu bar.
+    MenuBar,
+}
+
+/// Predefined menu items with a fixed label (e.g. `Quit`, `Copy`), plus `Separator`.
+///
+/// Apart from `Separator`, each is added like a custom item: clicking it delivers
+/// [`crate::event::Event::MenuEvent`] with its own id, and no OS action (quitting, copying, ...)
+/// is attached to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Quit,
+    Copy,
+    Paste,
+    Cut,
+    SelectAll,
+    Separator,
+}
+
+/// Describes a custom, application-defined menu item before it is added to a [`ContextMenu`].
+#[derive(Debug, Clone)]
+pub struct MenuItemAttributes {
+    pub(crate) label: String,
+    pub(crate) enabled: bool,
+    pub(crate) accelerator: Option<Accelerator>,
+}
+
+impl MenuItemAttributes {
+    /// Creates a new set of attributes for a plain, enabled menu item with `label`.
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            enabled: true,
+            accelerator: None,
+        }
+    }
+
+    /// Sets whether the item is enabled when it is added to a menu.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Attaches a keyboard shortcut that activates the item without it needing to be open.
+    ///
+    /// Only items added via [`ContextMenu::add_item`] get the shortcut;
+    /// [`ContextMenu::add_check_item`], [`ContextMenu::add_radio_item`] and
+    /// [`ContextMenu::add_icon_item`] ignore it.
+    pub fn with_accelerator(mut self, accelerator: Accelerator) -> Self {
+        self.accelerator = Some(accelerator);
+        self
+    }
+}
+
+/// A keyboard shortcut attached to a [`MenuItemAttributes`] via
+/// [`MenuItemAttributes::with_accelerator`], e.g. `Cmd+Q` or `Ctrl+O`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accelerator {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+/// An error produced when parsing an [`Accelerator`] from a string like `"CmdOrCtrl+Shift+S"`
+/// fails, because a modifier or key name wasn't recognized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceleratorParseError(pub(crate) String);
+
+impl FromStr for Accelerator {
+    type Err = AcceleratorParseError;
+
+    /// Parses shortcuts formatted as `+`-separated modifier names followed by a key name, e.g.
+    /// `"CmdOrCtrl+Shift+S"`. `CmdOrCtrl` resolves to [`ModifiersState::SUPER`] on macOS and
+    /// [`ModifiersState::CONTROL`] elsewhere, matching the convention used by most cross-platform
+    /// menu shortcuts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key_token, modifier_tokens) = parts
+            .split_last()
+            .ok_or_else(|| AcceleratorParseError(s.to_string()))?;
+
+        let mut modifiers = ModifiersState::empty();
+        for token in modifier_tokens {
+            modifiers |= match *token {
+                "CmdOrCtrl" => {
+                    if cfg!(target_os = "macos") {
+                        ModifiersState::SUPER
+                    } else {
+                        ModifiersState::CONTROL
+                    }
+                }
+                "Ctrl" | "Control" => ModifiersState::CONTROL,
+                "Shift" => ModifiersState::SHIFT,
+                "Alt" | "Option" => ModifiersState::ALT,
+                "Cmd" | "Super" => ModifiersState::SUPER,
+                other => return Err(AcceleratorParseError(format!("unknown modifier `{other}`"))),
+            };
+        }
+
+        let key = parse_key(key_token)
+            .ok_or_else(|| AcceleratorParseError(format!("unknown key `{key_token}`")))?;
+        Ok(Self { modifiers, key })
+    }
+}
+
+fn parse_key(token: &str) -> Option<KeyCode> {
+    use KeyCode::*;
+    Some(match token {
+        "A" => KeyA,
+        "B" => KeyB,
+        "C" => KeyC,
+        "D" => KeyD,
+        "E" => KeyE,
+        "F" => KeyF,
+        "G" => KeyG,
+        "H" => KeyH,
+        "I" => KeyI,
+        "J" => KeyJ,
+        "K" => KeyK,
+        "L" => KeyL,
+        "M" => KeyM,
+        "N" => KeyN,
+        "O" => KeyO,
+        "P" => KeyP,
+        "Q" => KeyQ,
+        "R" => KeyR,
+        "S" => KeyS,
+        "T" => KeyT,
+        "U" => KeyU,
+        "V" => KeyV,
+        "W" => KeyW,
+        "X" => KeyX,
+        "Y" => KeyY,
+        "Z" => KeyZ,
+        "0" => Digit0,
+        "1" => Digit1,
+        "2" => Digit2,
+        "3" => Digit3,
+        "4" => Digit4,
+        "5" => Digit5,
+        "6" => Digit6,
+        "7" => Digit7,
+        "8" => Digit8,
+        "9" => Digit9,
+        "F1" => F1,
+        "F2" => F2,
+        "F3" => F3,
+        "F4" => F4,
+        "F5" => F5,
+        "F6" => F6,
+        "F7" => F7,
+        "F8" => F8,
+        "F9" => F9,
+        "F10" => F10,
+        "F11" => F11,
+        "F12" => F12,
+        "Space" => Space,
+        "Enter" | "Return" => Enter,
+        "Escape" | "Esc" => Escape,
+        "Tab" => Tab,
+        "Backspace" => Backspace,
+        "Delete" => Delete,
+        "Up" => ArrowUp,
+        "Down" => ArrowDown,
+        "Left" => ArrowLeft,
+        "Right" => ArrowRight,
+        _ => return None,
+    })
+}
+
+/// A handle to a custom menu item that has already been added to a [`ContextMenu`].
+///
+/// Cloning a `CustomMenuItem` is cheap and yields another handle to the same underlying platform
+/// menu item (compare with [`MenuId::eq`] via [`CustomMenuItem::id`]).
+#[derive(Clone)]
+pub struct CustomMenuItem {
+    pub(crate) id: MenuId,
+    pub(crate) platform_item: crate::platform_impl::MenuItemHandle,
+}
+
+impl CustomMenuItem {
+    /// Returns the stable id delivered on [`crate::event::Event::MenuEvent`] for this item.
+    pub fn id(&self) -> MenuId {
+        self.id
+    }
+
+    /// Changes the item's displayed text.
+    pub fn set_label(&mut self, label: &str) {
+        self.platform_item.set_label(label);
+    }
+
+    /// Enables or disables (greys out) the item.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.platform_item.set_enabled(enabled);
+    }
+
+    /// Shows or hides the item.
+    ///
+    /// Win32 popup menus cannot hide an item, so on Windows this enables (`true`) or greys out
+    /// (`false`) the item instead.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.platform_item.set_visible(visible);
+    }
+}
+
+/// A handle to a checkable menu item added via [`ContextMenu::add_check_item`] or
+/// [`ContextMenu::add_radio_item`].
+///
+/// Like [`CustomMenuItem`], cloning yields another handle to the same underlying item; this is
+/// what lets a [`RadioGroup`] hold onto every member to uncheck siblings when one is selected.
+#[derive(Clone)]
+pub struct CheckMenuItem {
+    pub(crate) id: MenuId,
+    pub(crate) checked: Rc<Cell<bool>>,
+    pub(crate) platform_item: crate::platform_impl::CheckMenuItemHandle,
+}
+
+impl CheckMenuItem {
+    /// Returns the stable id delivered on [`crate::event::Event::MenuEvent`] for this item.
+    pub fn id(&self) -> MenuId {
+        self.id
+    }
+
+    /// Returns whether the item is currently checked.
+    pub fn is_checked(&self) -> bool {
+        self.checked.get()
+    }
+
+    /// Checks or unchecks the item.
+    ///
+    /// On macOS and Windows this changes only this item, also for a [`RadioGroup`] member: the
+    /// group's other members keep their state. On Linux, GTK unchecks the other members when a
+    /// radio item is checked.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.apply_checked(checked);
+    }
+
+    pub(crate) fn apply_checked(&self, checked: bool) {
+        self.checked.set(checked);
+        self.platform_item.set_checked(checked);
+    }
+}
+
+/// A handle to a menu item with an icon, added via [`ContextMenu::add_icon_item`].
+#[derive(Clone)]
+pub struct IconMenuItem {
+    pub(crate) id: MenuId,
+    pub(crate) platform_item: crate::platform_impl::IconMenuItemHandle,
+}
+
+impl IconMenuItem {
+    /// Returns the stable id delivered on [`crate::event::Event::MenuEvent`] for this item.
+    pub fn id(&self) -> MenuId {
+        self.id
+    }
+
+    /// Changes the item's displayed text.
+    pub fn set_label(&mut self, label: &str) {
+        self.platform_item.set_label(label);
+    }
+
+    /// Enables or disables (greys out) the item.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.platform_item.set_enabled(enabled);
+    }
+
+    /// Shows or hides the item.
+    ///
+    /// Win32 popup menus cannot hide an item, so on Windows this enables (`true`) or greys out
+    /// (`false`) the item instead.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.platform_item.set_visible(visible);
+    }
+
+    /// Replaces the item's icon.
+    pub fn set_icon(&mut self, icon: crate::system_tray::Icon) {
+        self.platform_item.set_icon(&icon);
+    }
+}
+
+/// A mutually-exclusive group of [`CheckMenuItem`]s added via [`ContextMenu::add_radio_item`]:
+/// clicking one to check it automatically unchecks every other member of the group (see
+/// [`CheckMenuItem::set_checked`] for checking one from code).
+#[derive(Clone, Default)]
+pub struct RadioGroup {
+    pub(crate) members: Rc<RefCell<Vec<CheckMenuItem>>>,
+}
+
+impl RadioGroup {
+    /// Creates an empty radio group. Items are added to it via
+    /// [`ContextMenu::add_radio_item`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `member_id` must be unchecked because `selected` was just checked, i.e. whether
+    /// it names a different member of the same radio group. Shared by the GTK/Win32/Cocoa click
+    /// handlers so the uncheck-siblings bookkeeping is defined (and tested) in one place.
+    pub(crate) fn is_sibling(member_id: MenuId, selected: MenuId) -> bool {
+        member_id != selected
+    }
+}
+
+/// A native context menu, attachable to a [`crate::system_tray::SystemTrayBuilder`].
+pub struct ContextMenu {
+    pub(crate) platform_menu: crate::platform_impl::Menu,
+}
+
+impl ContextMenu {
+    /// Creates an empty context menu.
+    pub fn new() -> Self {
+        Self {
+            platform_menu: crate::platform_impl::Menu::new(),
+        }
+    }
+
+    /// Appends a custom, application-handled menu item and returns a handle to it.
+    pub fn add_item(&mut self, attributes: MenuItemAttributes) -> CustomMenuItem {
+        let id = MenuId::next();
+        let platform_item = self.platform_menu.add_item(id, &attributes);
+        CustomMenuItem { id, platform_item }
+    }
+
+    /// Appends one of the predefined menu items (e.g. `Quit`).
+    ///
+    /// Returns `None` for [`MenuItem::Separator`], which gets no handle of its own.
+    pub fn add_native_item(&mut self, item: MenuItem) -> Option<CustomMenuItem> {
+        let id = MenuId::next();
+        self.platform_menu
+            .add_native_item(id, item)
+            .map(|platform_item| CustomMenuItem { id, platform_item })
+    }
+
+    /// Appends a standalone checkable menu item, initially `checked`.
+    ///
+    /// The check mark toggles automatically right before [`crate::event::Event::MenuEvent`] is
+    /// delivered for this item's click, so application code only needs to react to the event; it
+    /// doesn't need to call [`CheckMenuItem::set_checked`] itself in the common case.
+    pub fn add_check_item(&mut self, attributes: MenuItemAttributes, checked: bool) -> CheckMenuItem {
+        let id = MenuId::next();
+        let checked = Rc::new(Cell::new(checked));
+        let platform_item = self
+            .platform_menu
+            .add_check_item(id, &attributes, checked.clone(), None);
+        CheckMenuItem {
+            id,
+            checked,
+            platform_item,
+        }
+    }
+
+    /// Appends a checkable menu item belonging to `group`: checking it unchecks every other
+    /// member of the group, mirroring native radio-button menu semantics.
+    pub fn add_radio_item(
+        &mut self,
+        attributes: MenuItemAttributes,
+        group: &RadioGroup,
+        checked: bool,
+    ) -> CheckMenuItem {
+        let id = MenuId::next();
+        let checked = Rc::new(Cell::new(checked));
+        let platform_item =
+            self.platform_menu
+                .add_check_item(id, &attributes, checked.clone(), Some(group.members.clone()));
+        let item = CheckMenuItem {
+            id,
+            checked,
+            platform_item,
+        };
+        group.members.borrow_mut().push(item.clone());
+        item
+    }
+
+    /// Appends a custom menu item with a small icon drawn next to its label.
+    pub fn add_icon_item(
+        &mut self,
+        attributes: MenuItemAttributes,
+        icon: crate::system_tray::Icon,
+    ) -> IconMenuItem {
+        let id = MenuId::next();
+        let platform_item = self.platform_menu.add_icon_item(id, &attributes, &icon);
+        IconMenuItem { id, platform_item }
+    }
+}
+
+impl Default for ContextMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_key() {
+        let accelerator = Accelerator::from_str("Q").unwrap();
+        assert_eq!(accelerator.modifiers, ModifiersState::empty());
+        assert_eq!(accelerator.key, KeyCode::KeyQ);
+    }
+
+    #[test]
+    fn parses_multiple_modifiers() {
+        let accelerator = Accelerator::from_str("Ctrl+Shift+S").unwrap();
+        assert!(accelerator.modifiers.contains(ModifiersState::CONTROL));
+        assert!(accelerator.modifiers.contains(ModifiersState::SHIFT));
+        assert_eq!(accelerator.key, KeyCode::KeyS);
+    }
+
+    #[test]
+    fn resolves_cmd_or_ctrl_per_platform() {
+        let accelerator = Accelerator::from_str("CmdOrCtrl+Q").unwrap();
+        let expected = if cfg!(target_os = "macos") {
+            ModifiersState::SUPER
+        } else {
+            ModifiersState::CONTROL
+        };
+        assert_eq!(accelerator.modifiers, expected);
+    }
+
+    #[test]
+    fn parses_modifier_aliases() {
+        assert_eq!(
+            Accelerator::from_str("Option+A").unwrap().modifiers,
+            ModifiersState::ALT
+        );
+        assert_eq!(
+            Accelerator::from_str("Cmd+A").unwrap().modifiers,
+            ModifiersState::SUPER
+        );
+        assert_eq!(
+            Accelerator::from_str("Super+A").unwrap().modifiers,
+            ModifiersState::SUPER
+        );
+    }
+
+    #[test]
+    fn parses_function_and_arrow_keys() {
+        assert_eq!(Accelerator::from_str("F5").unwrap().key, KeyCode::F5);
+        assert_eq!(Accelerator::from_str("Up").unwrap().key, KeyCode::ArrowUp);
+        assert_eq!(Accelerator::from_str("Enter").unwrap().key, KeyCode::Enter);
+        assert_eq!(Accelerator::from_str("Return").unwrap().key, KeyCode::Enter);
+    }
+
+    #[test]
+    fn rejects_unknown_modifier() {
+        let err = Accelerator::from_str("Hyper+A").unwrap_err();
+        assert_eq!(err, AcceleratorParseError("unknown modifier `Hyper`".to_string()));
+    }
+
+    #[test]
+    fn rejects_unknown_key() {
+        let err = Accelerator::from_str("Ctrl+Foo").unwrap_err();
+        assert_eq!(err, AcceleratorParseError("unknown key `Foo`".to_string()));
+    }
+
+    #[test]
+    fn rejects_empty_string() {
+        let err = Accelerator::from_str("").unwrap_err();
+        assert_eq!(err, AcceleratorParseError("unknown key ``".to_string()));
+    }
+
+    #[test]
+    fn radio_group_uncheck_siblings_skips_the_selected_member() {
+        let selected = MenuId(2);
+        let ids = [MenuId(1), MenuId(2), MenuId(3)];
+        let siblings: Vec<MenuId> = ids
+            .into_iter()
+            .filter(|&id| RadioGroup::is_sibling(id, selected))
+            .collect();
+        assert_eq!(siblings, vec![MenuId(1), MenuId(3)]);
+    }
+
+    #[test]
+    fn radio_group_member_is_not_its_own_sibling() {
+        let id = MenuId(7);
+        assert!(!RadioGroup::is_sibling(id, id));
+    }
+}
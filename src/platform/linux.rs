@@ -0,0 +1,23 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Linux-only extensions.
+
+use std::path::Path;
+
+use crate::system_tray::SystemTrayBuilder;
+
+/// Linux-only extensions to [`SystemTrayBuilder`].
+pub trait SystemTrayBuilderExtLinux {
+    /// Sets the directory libappindicator writes its temporary icon files to. Needed because
+    /// `libappindicator` only accepts icon *paths*, not in-memory pixel buffers.
+    fn with_temp_icon_dir(self, dir: &Path) -> Self;
+}
+
+impl SystemTrayBuilderExtLinux for SystemTrayBuilder {
+    fn with_temp_icon_dir(mut self, dir: &Path) -> Self {
+        self.temp_icon_dir = Some(dir.to_path_buf());
+        self
+    }
+}
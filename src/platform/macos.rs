@@ -0,0 +1,64 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! macOS-only extensions.
+
+use crate::{event_loop::EventLoop, system_tray::SystemTrayBuilder};
+
+/// Mirrors `NSApplicationActivationPolicy`, controlling whether the app gets a Dock icon and
+/// menu bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationPolicy {
+    /// The application appears in the Dock and menu bar (the default for most apps).
+    Regular,
+    /// The application has no Dock presence; used for menu-bar-only (tray) apps like the
+    /// pomodoro example.
+    Accessory,
+    /// The application is fully hidden from the user.
+    Prohibited,
+}
+
+/// macOS-only extensions to [`EventLoop`].
+pub trait EventLoopExtMacOS {
+    /// Sets the activation policy before the app finishes launching. Must be called before
+    /// [`EventLoop::run`].
+    fn set_activation_policy(&mut self, activation_policy: ActivationPolicy);
+}
+
+impl<T: 'static> EventLoopExtMacOS for EventLoop<T> {
+    fn set_activation_policy(&mut self, activation_policy: ActivationPolicy) {
+        crate::platform_impl::set_activation_policy(activation_policy);
+    }
+}
+
+/// macOS-only extensions to [`SystemTrayBuilder`].
+pub trait SystemTrayBuilderExtMacOS {
+    /// Controls whether a left click opens the attached [`crate::menu::ContextMenu`] (the
+    /// default, matching `NSStatusItem`'s built-in behavior) or a right click does instead, in
+    /// which case a left click is delivered as a plain [`crate::event::TrayEvent::LeftClick`] so
+    /// the application can handle it itself. Either way, tao pops the menu up itself rather than
+    /// attaching it via `setMenu_`, so [`crate::event::TrayEvent`] is always dispatched regardless
+    /// of which click type opens the menu.
+    fn with_menu_on_left_click(self, enable: bool) -> Self;
+}
+
+impl SystemTrayBuilderExtMacOS for SystemTrayBuilder {
+    fn with_menu_on_left_click(mut self, enable: bool) -> Self {
+        self.menu_on_left_click = enable;
+        self
+    }
+}
+
+/// macOS-only extensions to [`crate::system_tray::SystemTray`].
+pub trait SystemTrayExtMacOS {
+    /// Returns a raw pointer to the underlying `NSStatusItem`, for apps that need to reach into
+    /// AppKit APIs tao doesn't expose directly.
+    fn ns_status_item(&self) -> *mut std::ffi::c_void;
+}
+
+impl SystemTrayExtMacOS for crate::system_tray::SystemTray {
+    fn ns_status_item(&self) -> *mut std::ffi::c_void {
+        self.platform_tray.ns_status_item()
+    }
+}
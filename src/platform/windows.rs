@@ -0,0 +1,27 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Windows-only extensions.
+
+use winapi::shared::windef::HACCEL;
+
+use crate::menu::ContextMenu;
+
+/// Windows-only extensions to [`ContextMenu`].
+pub trait ContextMenuExtWindows {
+    /// Builds a Win32 accelerator table (`HACCEL`) from every
+    /// [`crate::menu::MenuItemAttributes::with_accelerator`] attached to this menu's items.
+    ///
+    /// Unlike macOS/GTK, `HMENU` shortcuts aren't dispatched by the OS on their own: the owning
+    /// window's message pump must call `TranslateAcceleratorW(hwnd, haccel, &msg)` on every
+    /// message before `TranslateMessage`/`DispatchMessageW`, using the `HWND` that owns this
+    /// menu and the table returned here.
+    fn haccel(&self) -> HACCEL;
+}
+
+impl ContextMenuExtWindows for ContextMenu {
+    fn haccel(&self) -> HACCEL {
+        self.platform_menu.build_haccel()
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The original `libappindicator`/GTK status icon backend, kept as a fallback (via the
+//! `ayatana` feature) for desktop environments with no StatusNotifierWatcher running — see
+//! [`super::sni`] for the default, dependency-free backend.
+
+use std::path::{Path, PathBuf};
+
+use gtk::prelude::*;
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::TrayEvent,
+    menu::ContextMenu,
+    system_tray::{Icon, OsError, Rectangle},
+    TrayId,
+};
+
+pub(crate) struct SystemTray {
+    indicator: libappindicator::AppIndicator,
+}
+
+impl SystemTray {
+    pub(crate) fn new(
+        id: TrayId,
+        icon: &Icon,
+        tray_menu: Option<ContextMenu>,
+        tooltip: Option<&str>,
+        _title: Option<&str>,
+        temp_icon_dir: Option<&Path>,
+    ) -> Result<Self, OsError> {
+        let icon_path = write_temp_icon(icon, temp_icon_dir).map_err(|e| e.to_string())?;
+
+        let mut indicator = libappindicator::AppIndicator::new("tao-application", "");
+        indicator.set_status(libappindicator::AppIndicatorStatus::Active);
+        indicator.set_icon_theme_path(icon_path.parent().unwrap().to_str().unwrap_or(""));
+        indicator.set_icon_full(icon_path.file_stem().unwrap().to_str().unwrap_or(""), "icon");
+
+        // AppIndicator has no tooltip API of its own; `tooltip` is accepted for API parity with
+        // Windows/macOS and silently ignored here.
+        let _ = tooltip;
+
+        if let Some(menu) = tray_menu {
+            indicator.set_menu(&mut menu.platform_menu.gtk_menu.clone());
+        }
+
+        if let Some(status_icon) = indicator.status_icon() {
+            status_icon.connect_button_press_event(move |_, event| {
+                let (x, y) = event.root();
+                let position = PhysicalPosition::new(x, y);
+                let bounds = Rectangle {
+                    position,
+                    size: PhysicalSize::new(0.0, 0.0),
+                };
+                let click = if event.event_type() == gdk::EventType::DoubleButtonPress {
+                    Some(TrayEvent::DoubleClick { position, bounds })
+                } else {
+                    match event.button() {
+                        1 => Some(TrayEvent::LeftClick),
+                        3 => Some(TrayEvent::RightClick { position, bounds }),
+                        _ => None,
+                    }
+                };
+                if let Some(click) = click {
+                    crate::platform_impl::dispatch_tray_event(id, click);
+                }
+                glib::Propagation::Proceed
+            });
+        }
+
+        Ok(Self { indicator })
+    }
+
+    pub(crate) fn set_icon(&mut self, icon: Icon) {
+        if let Ok(icon_path) = write_temp_icon(&icon, None) {
+            self.indicator
+                .set_icon_full(icon_path.file_stem().unwrap().to_str().unwrap_or(""), "icon");
+        }
+    }
+
+    pub(crate) fn set_title(&mut self, _title: &str) {
+        // AppIndicator has no menu-bar title; this is a no-op on Linux.
+    }
+
+    pub(crate) fn set_menu(&mut self, menu: Option<ContextMenu>) {
+        match menu {
+            Some(menu) => self.indicator.set_menu(&mut menu.platform_menu.gtk_menu.clone()),
+            None => self.indicator.set_menu(&mut gtk::Menu::new()),
+        }
+    }
+}
+
+/// `libappindicator` only accepts icon *paths*, not in-memory pixel buffers, so every icon update
+/// is written out to a temp file first (see [`crate::platform::linux::SystemTrayBuilderExtLinux`]).
+fn write_temp_icon(icon: &Icon, dir: Option<&Path>) -> std::io::Result<PathBuf> {
+    let dir = dir.map(Path::to_path_buf).unwrap_or_else(std::env::temp_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("tao-tray-icon-{}.png", std::process::id()));
+    image::save_buffer(&path, &icon.rgba, icon.width, icon.height, image::ColorType::Rgba8)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    Ok(path)
+}
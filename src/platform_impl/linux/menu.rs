@@ -0,0 +1,335 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `GtkMenu` tree backing a [`crate::menu::ContextMenu`], shared by both Linux tray backends
+//! (see [`super::sni`] and [`super::ayatana`]): it is the canonical representation of a tray's
+//! menu, walked by [`super::sni`] to serialize a DBusMenu layout and handed directly to
+//! `libappindicator` by [`super::ayatana`].
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use gtk::prelude::*;
+
+use crate::{
+    keyboard::{KeyCode, ModifiersState},
+    menu::{CheckMenuItem, MenuId, MenuItem, MenuItemAttributes, MenuType, RadioGroup},
+    system_tray::Icon,
+};
+
+/// The `glib` object-data key each menu item widget's originating [`MenuId`] is stashed under, so
+/// [`super::sni::DBusMenu`] can recover it when walking the tree to answer `GetLayout`.
+pub(crate) const MENU_ID_DATA_KEY: &str = "tao-menu-id";
+
+/// The `glib` object-data key a radio group member's `connect_toggled` closure stashes on itself
+/// right before programmatically unchecking it, so that closure (re-entered by GTK's "toggled"
+/// signal, which fires on every active-state change, not just user clicks) knows to skip
+/// redelivering it as a [`crate::event::Event::MenuEvent`].
+const SUPPRESS_TOGGLE_DISPATCH_DATA_KEY: &str = "tao-suppress-toggle-dispatch";
+
+/// A `GtkMenu` plus the callbacks needed to redeliver clicks as [`crate::event::Event::MenuEvent`].
+pub(crate) struct Menu {
+    pub(crate) gtk_menu: gtk::Menu,
+    accel_group: gtk::AccelGroup,
+}
+
+impl Menu {
+    pub(crate) fn new() -> Self {
+        let accel_group = gtk::AccelGroup::new();
+        let gtk_menu = gtk::Menu::new();
+        gtk_menu.set_accel_group(Some(&accel_group));
+        Self {
+            gtk_menu,
+            accel_group,
+        }
+    }
+
+    pub(crate) fn add_item(&mut self, id: MenuId, attributes: &MenuItemAttributes) -> MenuItemHandle {
+        let item = gtk::MenuItem::with_label(&attributes.label);
+        item.set_sensitive(attributes.enabled);
+        unsafe { item.set_data(MENU_ID_DATA_KEY, id.0) };
+        if let Some(accelerator) = &attributes.accelerator {
+            item.add_accelerator(
+                "activate",
+                &self.accel_group,
+                keycode_to_gdk_keyval(accelerator.key),
+                modifiers_to_gdk(accelerator.modifiers),
+                gtk::AccelFlags::VISIBLE,
+            );
+        }
+        item.connect_activate(move |_| {
+            crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+        });
+        self.gtk_menu.append(&item);
+        item.show();
+        MenuItemHandle { gtk_item: item }
+    }
+
+    pub(crate) fn add_native_item(&mut self, id: MenuId, item: MenuItem) -> Option<MenuItemHandle> {
+        if item == MenuItem::Separator {
+            let separator = gtk::SeparatorMenuItem::new();
+            unsafe { separator.set_data(MENU_ID_DATA_KEY, id.0) };
+            self.gtk_menu.append(&separator);
+            return None;
+        }
+        let label = match item {
+            MenuItem::Quit => "Quit",
+            MenuItem::Copy => "Copy",
+            MenuItem::Paste => "Paste",
+            MenuItem::Cut => "Cut",
+            MenuItem::SelectAll => "Select All",
+            MenuItem::Separator => unreachable!(),
+        };
+        let gtk_item = gtk::MenuItem::with_label(label);
+        unsafe { gtk_item.set_data(MENU_ID_DATA_KEY, id.0) };
+        gtk_item.connect_activate(move |_| {
+            crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+        });
+        self.gtk_menu.append(&gtk_item);
+        gtk_item.show();
+        Some(MenuItemHandle { gtk_item })
+    }
+
+    pub(crate) fn add_check_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        checked: Rc<Cell<bool>>,
+        group: Option<Rc<RefCell<Vec<CheckMenuItem>>>>,
+    ) -> CheckMenuItemHandle {
+        let gtk_item: gtk::CheckMenuItem = match &group {
+            Some(group) => match group.borrow().first() {
+                Some(sibling) => {
+                    let sibling_radio = sibling
+                        .platform_item
+                        .gtk_item
+                        .clone()
+                        .downcast::<gtk::RadioMenuItem>()
+                        .expect("radio group members are always GtkRadioMenuItem");
+                    gtk::RadioMenuItem::from_widget(&sibling_radio).upcast()
+                }
+                None => gtk::RadioMenuItem::with_label(&attributes.label).upcast(),
+            },
+            None => gtk::CheckMenuItem::with_label(&attributes.label),
+        };
+        gtk_item.set_label(&attributes.label);
+        gtk_item.set_sensitive(attributes.enabled);
+        gtk_item.set_active(checked.get());
+        unsafe { gtk_item.set_data(MENU_ID_DATA_KEY, id.0) };
+
+        gtk_item.connect_toggled(move |gtk_item| {
+            // GTK's "toggled" signal fires on every active-state change, including the
+            // programmatic uncheck this closure itself issues on radio-group siblings below; skip
+            // redelivering those so exactly one `MenuEvent` fires per user click, matching the
+            // macOS/Windows radio-group handlers.
+            if unsafe { gtk_item.steal_data::<()>(SUPPRESS_TOGGLE_DISPATCH_DATA_KEY) }.is_some() {
+                return;
+            }
+
+            let now_checked = gtk_item.is_active();
+            checked.set(now_checked);
+            // GTK itself deactivates the previously active radio sibling (emitting "toggled" on it)
+            // before activating the clicked one; only the newly active member reports the click.
+            if group.is_some() && !now_checked {
+                return;
+            }
+            if now_checked {
+                if let Some(group) = &group {
+                    for member in group.borrow().iter() {
+                        if RadioGroup::is_sibling(member.id, id) && member.is_checked() {
+                            unsafe {
+                                member
+                                    .platform_item
+                                    .gtk_item
+                                    .set_data(SUPPRESS_TOGGLE_DISPATCH_DATA_KEY, ());
+                            }
+                            member.apply_checked(false);
+                        }
+                    }
+                }
+            }
+            crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+        });
+
+        self.gtk_menu.append(&gtk_item);
+        gtk_item.show();
+        CheckMenuItemHandle { gtk_item }
+    }
+
+    pub(crate) fn add_icon_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        icon: &Icon,
+    ) -> IconMenuItemHandle {
+        let gtk_item = gtk::ImageMenuItem::with_label(&attributes.label);
+        gtk_item.set_sensitive(attributes.enabled);
+        gtk_item.set_image(Some(&icon_to_gtk_image(icon)));
+        gtk_item.set_always_show_image(true);
+        unsafe { gtk_item.set_data(MENU_ID_DATA_KEY, id.0) };
+        gtk_item.connect_activate(move |_| {
+            crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+        });
+        self.gtk_menu.append(&gtk_item);
+        gtk_item.show();
+        IconMenuItemHandle { gtk_item }
+    }
+}
+
+/// Converts a modifier set into the `gdk::ModifierType` bits `gtk_widget_add_accelerator` expects.
+fn modifiers_to_gdk(modifiers: ModifiersState) -> gdk::ModifierType {
+    let mut gdk_mods = gdk::ModifierType::empty();
+    if modifiers.contains(ModifiersState::SHIFT) {
+        gdk_mods |= gdk::ModifierType::SHIFT_MASK;
+    }
+    if modifiers.contains(ModifiersState::CONTROL) {
+        gdk_mods |= gdk::ModifierType::CONTROL_MASK;
+    }
+    if modifiers.contains(ModifiersState::ALT) {
+        gdk_mods |= gdk::ModifierType::MOD1_MASK;
+    }
+    if modifiers.contains(ModifiersState::SUPER) {
+        gdk_mods |= gdk::ModifierType::SUPER_MASK;
+    }
+    gdk_mods
+}
+
+/// Converts a [`KeyCode`] into the X11 keysym `gtk_widget_add_accelerator` expects.
+fn keycode_to_gdk_keyval(key: KeyCode) -> u32 {
+    use KeyCode::*;
+    match key {
+        KeyA => gdk::keys::constants::a.into(),
+        KeyB => gdk::keys::constants::b.into(),
+        KeyC => gdk::keys::constants::c.into(),
+        KeyD => gdk::keys::constants::d.into(),
+        KeyE => gdk::keys::constants::e.into(),
+        KeyF => gdk::keys::constants::f.into(),
+        KeyG => gdk::keys::constants::g.into(),
+        KeyH => gdk::keys::constants::h.into(),
+        KeyI => gdk::keys::constants::i.into(),
+        KeyJ => gdk::keys::constants::j.into(),
+        KeyK => gdk::keys::constants::k.into(),
+        KeyL => gdk::keys::constants::l.into(),
+        KeyM => gdk::keys::constants::m.into(),
+        KeyN => gdk::keys::constants::n.into(),
+        KeyO => gdk::keys::constants::o.into(),
+        KeyP => gdk::keys::constants::p.into(),
+        KeyQ => gdk::keys::constants::q.into(),
+        KeyR => gdk::keys::constants::r.into(),
+        KeyS => gdk::keys::constants::s.into(),
+        KeyT => gdk::keys::constants::t.into(),
+        KeyU => gdk::keys::constants::u.into(),
+        KeyV => gdk::keys::constants::v.into(),
+        KeyW => gdk::keys::constants::w.into(),
+        KeyX => gdk::keys::constants::x.into(),
+        KeyY => gdk::keys::constants::y.into(),
+        KeyZ => gdk::keys::constants::z.into(),
+        Digit0 => gdk::keys::constants::_0.into(),
+        Digit1 => gdk::keys::constants::_1.into(),
+        Digit2 => gdk::keys::constants::_2.into(),
+        Digit3 => gdk::keys::constants::_3.into(),
+        Digit4 => gdk::keys::constants::_4.into(),
+        Digit5 => gdk::keys::constants::_5.into(),
+        Digit6 => gdk::keys::constants::_6.into(),
+        Digit7 => gdk::keys::constants::_7.into(),
+        Digit8 => gdk::keys::constants::_8.into(),
+        Digit9 => gdk::keys::constants::_9.into(),
+        F1 => gdk::keys::constants::F1.into(),
+        F2 => gdk::keys::constants::F2.into(),
+        F3 => gdk::keys::constants::F3.into(),
+        F4 => gdk::keys::constants::F4.into(),
+        F5 => gdk::keys::constants::F5.into(),
+        F6 => gdk::keys::constants::F6.into(),
+        F7 => gdk::keys::constants::F7.into(),
+        F8 => gdk::keys::constants::F8.into(),
+        F9 => gdk::keys::constants::F9.into(),
+        F10 => gdk::keys::constants::F10.into(),
+        F11 => gdk::keys::constants::F11.into(),
+        F12 => gdk::keys::constants::F12.into(),
+        Space => gdk::keys::constants::space.into(),
+        Enter => gdk::keys::constants::Return.into(),
+        Escape => gdk::keys::constants::Escape.into(),
+        Tab => gdk::keys::constants::Tab.into(),
+        Backspace => gdk::keys::constants::BackSpace.into(),
+        Delete => gdk::keys::constants::Delete.into(),
+        ArrowUp => gdk::keys::constants::Up.into(),
+        ArrowDown => gdk::keys::constants::Down.into(),
+        ArrowLeft => gdk::keys::constants::Left.into(),
+        ArrowRight => gdk::keys::constants::Right.into(),
+    }
+}
+
+/// Converts `icon`'s RGBA buffer into a `gtk::Image` backed by a `gdk_pixbuf::Pixbuf`.
+pub(crate) fn icon_to_gtk_image(icon: &Icon) -> gtk::Image {
+    let rowstride = icon.width as i32 * 4;
+    let pixbuf = gdk_pixbuf::Pixbuf::from_mut_slice(
+        icon.rgba.clone(),
+        gdk_pixbuf::Colorspace::Rgb,
+        true,
+        8,
+        icon.width as i32,
+        icon.height as i32,
+        rowstride,
+    );
+    gtk::Image::from_pixbuf(Some(&pixbuf))
+}
+
+/// A handle to a single `GtkMenuItem`, kept alive for the lifetime of its owning [`crate::menu::ContextMenu`].
+#[derive(Clone)]
+pub(crate) struct MenuItemHandle {
+    gtk_item: gtk::MenuItem,
+}
+
+impl MenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        self.gtk_item.set_label(label);
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.gtk_item.set_sensitive(enabled);
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        self.gtk_item.set_visible(visible);
+    }
+}
+
+/// A handle to a `GtkCheckMenuItem` (or the `GtkRadioMenuItem` that extends it, for radio-group
+/// members).
+#[derive(Clone)]
+pub(crate) struct CheckMenuItemHandle {
+    gtk_item: gtk::CheckMenuItem,
+}
+
+impl CheckMenuItemHandle {
+    pub(crate) fn set_checked(&self, checked: bool) {
+        self.gtk_item.set_active(checked);
+    }
+}
+
+/// A handle to a `GtkImageMenuItem`.
+#[derive(Clone)]
+pub(crate) struct IconMenuItemHandle {
+    gtk_item: gtk::ImageMenuItem,
+}
+
+impl IconMenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        self.gtk_item.set_label(label);
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.gtk_item.set_sensitive(enabled);
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        self.gtk_item.set_visible(visible);
+    }
+
+    pub(crate) fn set_icon(&self, icon: &Icon) {
+        self.gtk_item.set_image(Some(&icon_to_gtk_image(icon)));
+    }
+}
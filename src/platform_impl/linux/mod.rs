@@ -0,0 +1,87 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Linux backend. [`menu`] holds the `GtkMenu` tree shared by both tray backends: [`sni`], a
+//! pure-Rust StatusNotifierItem/DBusMenu implementation over `zbus` used whenever a
+//! StatusNotifierWatcher is running, and [`ayatana`], the original `libappindicator` backend kept
+//! as a fallback (behind the `ayatana` feature) for desktops with no watcher.
+
+use std::path::Path;
+
+use crate::{menu::ContextMenu, system_tray::{Icon, OsError}, TrayId};
+
+mod menu;
+pub(crate) use menu::*;
+
+#[cfg(feature = "ayatana")]
+mod ayatana;
+mod sni;
+
+/// Selects between the [`sni`] and (if enabled) [`ayatana`] backends at construction time, based
+/// on whether a StatusNotifierWatcher is present on the session bus.
+pub(crate) enum SystemTray {
+    Sni(sni::SystemTray),
+    #[cfg(feature = "ayatana")]
+    Ayatana(ayatana::SystemTray),
+}
+
+impl SystemTray {
+    pub(crate) fn new(
+        id: TrayId,
+        icon: &Icon,
+        tray_menu: Option<ContextMenu>,
+        tooltip: Option<&str>,
+        title: Option<&str>,
+        temp_icon_dir: Option<&Path>,
+    ) -> Result<Self, OsError> {
+        if sni::is_watcher_registered() {
+            return Ok(Self::Sni(sni::SystemTray::new(id, icon, tray_menu, tooltip, title)?));
+        }
+
+        #[cfg(feature = "ayatana")]
+        {
+            return Ok(Self::Ayatana(ayatana::SystemTray::new(
+                id,
+                icon,
+                tray_menu,
+                tooltip,
+                title,
+                temp_icon_dir,
+            )?));
+        }
+
+        #[cfg(not(feature = "ayatana"))]
+        {
+            let _ = temp_icon_dir;
+            Err(OsError(
+                "no org.kde.StatusNotifierWatcher is running, and the `ayatana` fallback feature is disabled"
+                    .into(),
+            ))
+        }
+    }
+
+    pub(crate) fn set_icon(&mut self, icon: Icon) {
+        match self {
+            Self::Sni(tray) => tray.set_icon(icon),
+            #[cfg(feature = "ayatana")]
+            Self::Ayatana(tray) => tray.set_icon(icon),
+        }
+    }
+
+    pub(crate) fn set_title(&mut self, title: &str) {
+        match self {
+            Self::Sni(tray) => tray.set_title(title),
+            #[cfg(feature = "ayatana")]
+            Self::Ayatana(tray) => tray.set_title(title),
+        }
+    }
+
+    pub(crate) fn set_menu(&mut self, menu: Option<ContextMenu>) {
+        match self {
+            Self::Sni(tray) => tray.set_menu(menu),
+            #[cfg(feature = "ayatana")]
+            Self::Ayatana(tray) => tray.set_menu(menu),
+        }
+    }
+}
@@ -0,0 +1,641 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! AppKit (`NSStatusItem`/`NSMenu`) backend.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Once,
+};
+
+use cocoa::{
+    appkit::{NSApp, NSApplication, NSApplicationActivationPolicy, NSMenu, NSMenuItem, NSStatusBar, NSStatusItem},
+    base::{id, nil, NO, YES},
+    foundation::{NSSize, NSString},
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    rc::autoreleasepool,
+    runtime::{Class, Object, Sel},
+    sel, sel_impl,
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::TrayEvent,
+    keyboard::{KeyCode, ModifiersState},
+    menu::{Accelerator, CheckMenuItem, ContextMenu, MenuId, MenuItem, MenuItemAttributes, MenuType, RadioGroup},
+    platform::macos::ActivationPolicy,
+    system_tray::{Icon, OsError, Rectangle},
+    TrayId,
+};
+
+pub(crate) fn set_activation_policy(policy: ActivationPolicy) {
+    let ns_policy = match policy {
+        ActivationPolicy::Regular => NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular,
+        ActivationPolicy::Accessory => NSApplicationActivationPolicy::NSApplicationActivationPolicyAccessory,
+        ActivationPolicy::Prohibited => NSApplicationActivationPolicy::NSApplicationActivationPolicyProhibited,
+    };
+    unsafe {
+        NSApp().setActivationPolicy_(ns_policy);
+    }
+}
+
+/// An `NSObject` subclass whose sole `taoMenuItemSelected:` action forwards the clicked item's
+/// [`MenuId`] (stashed in an associated ivar) to [`crate::platform_impl::dispatch_menu_event`].
+fn menu_target_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let superclass = Class::get("NSObject").unwrap();
+        let mut decl = ClassDecl::new("TaoMenuItemTarget", superclass).unwrap();
+        decl.add_ivar::<u32>("taoMenuId");
+        decl.add_method(
+            sel!(taoMenuItemSelected:),
+            menu_item_selected as extern "C" fn(&Object, Sel, id),
+        );
+        decl.register();
+    });
+    Class::get("TaoMenuItemTarget").unwrap()
+}
+
+extern "C" fn menu_item_selected(this: &Object, _cmd: Sel, _sender: id) {
+    let menu_id: u32 = unsafe { *this.get_ivar("taoMenuId") };
+    crate::platform_impl::dispatch_menu_event(MenuId(menu_id as u16), MenuType::ContextMenu);
+}
+
+fn register_menu_item_target(ns_item: id, id: MenuId) {
+    unsafe {
+        let target: id = msg_send![menu_target_class(), new];
+        (*target).set_ivar("taoMenuId", id.0 as u32);
+        let _: () = msg_send![ns_item, setTarget: target];
+    }
+}
+
+/// State tracked per checkable item so [`check_item_selected`] can flip it and its siblings
+/// without AppKit's target/action mechanism being able to carry Rust closures.
+struct CheckItemState {
+    ns_item: id,
+    checked: Rc<Cell<bool>>,
+    group: Option<Rc<RefCell<Vec<CheckMenuItem>>>>,
+}
+
+thread_local! {
+    static CHECK_ITEMS: RefCell<std::collections::HashMap<u16, CheckItemState>> =
+        RefCell::new(std::collections::HashMap::new());
+}
+
+/// An `NSObject` subclass whose action looks up the clicked item's [`CheckItemState`] (by the
+/// [`MenuId`] stashed in its ivar), flips its `NSControlStateValueOn`/`Off` state and that of any
+/// radio-group siblings, and only then forwards the click to
+/// [`crate::platform_impl::dispatch_menu_event`] — menu bar items don't auto-toggle their own
+/// check mark the way `GtkCheckMenuItem` does, so tao must do it explicitly here.
+fn check_item_target_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let superclass = Class::get("NSObject").unwrap();
+        let mut decl = ClassDecl::new("TaoCheckMenuItemTarget", superclass).unwrap();
+        decl.add_ivar::<u32>("taoMenuId");
+        decl.add_method(
+            sel!(taoCheckMenuItemSelected:),
+            check_item_selected as extern "C" fn(&Object, Sel, id),
+        );
+        decl.register();
+    });
+    Class::get("TaoCheckMenuItemTarget").unwrap()
+}
+
+extern "C" fn check_item_selected(this: &Object, _cmd: Sel, _sender: id) {
+    let raw_id: u32 = unsafe { *this.get_ivar("taoMenuId") };
+    let id = MenuId(raw_id as u16);
+
+    CHECK_ITEMS.with(|items| {
+        if let Some(state) = items.borrow().get(&(raw_id as u16)) {
+            let now_checked = !state.checked.get();
+            state.checked.set(now_checked);
+            unsafe { set_ns_item_checked(state.ns_item, now_checked) };
+            if now_checked {
+                if let Some(group) = &state.group {
+                    for member in group.borrow().iter() {
+                        if RadioGroup::is_sibling(member.id, id) {
+                            member.apply_checked(false);
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+}
+
+fn register_check_item_target(ns_item: id, id: MenuId) {
+    unsafe {
+        let target: id = msg_send![check_item_target_class(), new];
+        (*target).set_ivar("taoMenuId", id.0 as u32);
+        let _: () = msg_send![ns_item, setTarget: target];
+    }
+}
+
+unsafe fn set_ns_item_checked(ns_item: id, checked: bool) {
+    // `NSControlStateValueOn` / `NSControlStateValueOff`.
+    let state: i64 = if checked { 1 } else { 0 };
+    let _: () = msg_send![ns_item, setState: state];
+}
+
+pub(crate) struct Menu {
+    pub(crate) ns_menu: id,
+}
+
+impl Menu {
+    pub(crate) fn new() -> Self {
+        let ns_menu = unsafe { NSMenu::new(nil).autorelease() };
+        Self { ns_menu }
+    }
+
+    pub(crate) fn add_item(&mut self, id: MenuId, attributes: &MenuItemAttributes) -> MenuItemHandle {
+        let ns_item = unsafe {
+            let title = NSString::alloc(nil).init_str(&attributes.label);
+            let item = NSMenuItem::alloc(nil).initWithTitle_action_keyEquivalent_(
+                title,
+                sel!(taoMenuItemSelected:),
+                NSString::alloc(nil).init_str(""),
+            );
+            item.setEnabled_(attributes.enabled as i8);
+            if let Some(accelerator) = &attributes.accelerator {
+                apply_accelerator(item, accelerator);
+            }
+            NSMenu::addItem_(self.ns_menu, item);
+            item
+        };
+        register_menu_item_target(ns_item, id);
+        MenuItemHandle { ns_item }
+    }
+
+    pub(crate) fn add_native_item(&mut self, id: MenuId, item: MenuItem) -> Option<MenuItemHandle> {
+        if item == MenuItem::Separator {
+            unsafe {
+                NSMenu::addItem_(self.ns_menu, NSMenuItem::separatorItem(nil));
+            }
+            return None;
+        }
+        let label = match item {
+            MenuItem::Quit => "Quit",
+            MenuItem::Copy => "Copy",
+            MenuItem::Paste => "Paste",
+            MenuItem::Cut => "Cut",
+            MenuItem::SelectAll => "Select All",
+            MenuItem::Separator => unreachable!(),
+        };
+        let ns_item = unsafe {
+            let title = NSString::alloc(nil).init_str(label);
+            let item = NSMenuItem::alloc(nil).initWithTitle_action_keyEquivalent_(
+                title,
+                sel!(taoMenuItemSelected:),
+                NSString::alloc(nil).init_str(""),
+            );
+            NSMenu::addItem_(self.ns_menu, item);
+            item
+        };
+        register_menu_item_target(ns_item, id);
+        Some(MenuItemHandle { ns_item })
+    }
+
+    pub(crate) fn add_check_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        checked: Rc<Cell<bool>>,
+        group: Option<Rc<RefCell<Vec<CheckMenuItem>>>>,
+    ) -> CheckMenuItemHandle {
+        let ns_item = unsafe {
+            let title = NSString::alloc(nil).init_str(&attributes.label);
+            let item = NSMenuItem::alloc(nil).initWithTitle_action_keyEquivalent_(
+                title,
+                sel!(taoCheckMenuItemSelected:),
+                NSString::alloc(nil).init_str(""),
+            );
+            item.setEnabled_(attributes.enabled as i8);
+            set_ns_item_checked(item, checked.get());
+            NSMenu::addItem_(self.ns_menu, item);
+            item
+        };
+        register_check_item_target(ns_item, id);
+        CHECK_ITEMS.with(|items| {
+            items.borrow_mut().insert(
+                id.0,
+                CheckItemState {
+                    ns_item,
+                    checked,
+                    group,
+                },
+            );
+        });
+        CheckMenuItemHandle { ns_item }
+    }
+
+    pub(crate) fn add_icon_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        icon: &Icon,
+    ) -> IconMenuItemHandle {
+        let ns_item = unsafe {
+            let title = NSString::alloc(nil).init_str(&attributes.label);
+            let item = NSMenuItem::alloc(nil).initWithTitle_action_keyEquivalent_(
+                title,
+                sel!(taoMenuItemSelected:),
+                NSString::alloc(nil).init_str(""),
+            );
+            item.setEnabled_(attributes.enabled as i8);
+            let ns_image = icon_to_ns_image(icon);
+            let _: () = msg_send![item, setImage: ns_image];
+            NSMenu::addItem_(self.ns_menu, item);
+            item
+        };
+        register_menu_item_target(ns_item, id);
+        IconMenuItemHandle { ns_item }
+    }
+}
+
+/// Sets `item`'s `keyEquivalent`/`keyEquivalentModifierMask` from `accelerator`; AppKit dispatches
+/// the shortcut itself once these are set, without tao needing to intercept key events.
+unsafe fn apply_accelerator(item: id, accelerator: &Accelerator) {
+    let key_equivalent = NSString::alloc(nil).init_str(&key_equivalent_string(accelerator.key));
+    let _: () = msg_send![item, setKeyEquivalent: key_equivalent];
+    let _: () = msg_send![item, setKeyEquivalentModifierMask: modifiers_to_ns(accelerator.modifiers)];
+}
+
+/// Converts a modifier set into the `NSEventModifierFlags` bits `keyEquivalentModifierMask` expects.
+fn modifiers_to_ns(modifiers: ModifiersState) -> u64 {
+    const NS_SHIFT_KEY_MASK: u64 = 1 << 17;
+    const NS_CONTROL_KEY_MASK: u64 = 1 << 18;
+    const NS_ALTERNATE_KEY_MASK: u64 = 1 << 19;
+    const NS_COMMAND_KEY_MASK: u64 = 1 << 20;
+
+    let mut mask = 0;
+    if modifiers.contains(ModifiersState::SHIFT) {
+        mask |= NS_SHIFT_KEY_MASK;
+    }
+    if modifiers.contains(ModifiersState::CONTROL) {
+        mask |= NS_CONTROL_KEY_MASK;
+    }
+    if modifiers.contains(ModifiersState::ALT) {
+        mask |= NS_ALTERNATE_KEY_MASK;
+    }
+    if modifiers.contains(ModifiersState::SUPER) {
+        mask |= NS_COMMAND_KEY_MASK;
+    }
+    mask
+}
+
+/// Converts a [`KeyCode`] into the single-character string AppKit's `keyEquivalent` expects;
+/// non-printable keys use the Unicode "function key" code points AppKit reserves for them.
+fn key_equivalent_string(key: KeyCode) -> String {
+    use KeyCode::*;
+    let ch = match key {
+        KeyA => 'a',
+        KeyB => 'b',
+        KeyC => 'c',
+        KeyD => 'd',
+        KeyE => 'e',
+        KeyF => 'f',
+        KeyG => 'g',
+        KeyH => 'h',
+        KeyI => 'i',
+        KeyJ => 'j',
+        KeyK => 'k',
+        KeyL => 'l',
+        KeyM => 'm',
+        KeyN => 'n',
+        KeyO => 'o',
+        KeyP => 'p',
+        KeyQ => 'q',
+        KeyR => 'r',
+        KeyS => 's',
+        KeyT => 't',
+        KeyU => 'u',
+        KeyV => 'v',
+        KeyW => 'w',
+        KeyX => 'x',
+        KeyY => 'y',
+        KeyZ => 'z',
+        Digit0 => '0',
+        Digit1 => '1',
+        Digit2 => '2',
+        Digit3 => '3',
+        Digit4 => '4',
+        Digit5 => '5',
+        Digit6 => '6',
+        Digit7 => '7',
+        Digit8 => '8',
+        Digit9 => '9',
+        F1 => '\u{F704}',
+        F2 => '\u{F705}',
+        F3 => '\u{F706}',
+        F4 => '\u{F707}',
+        F5 => '\u{F708}',
+        F6 => '\u{F709}',
+        F7 => '\u{F70A}',
+        F8 => '\u{F70B}',
+        F9 => '\u{F70C}',
+        F10 => '\u{F70D}',
+        F11 => '\u{F70E}',
+        F12 => '\u{F70F}',
+        Space => ' ',
+        Enter => '\r',
+        Escape => '\u{1b}',
+        Tab => '\t',
+        Backspace => '\u{8}',
+        Delete => '\u{7f}',
+        ArrowUp => '\u{F700}',
+        ArrowDown => '\u{F701}',
+        ArrowLeft => '\u{F702}',
+        ArrowRight => '\u{F703}',
+    };
+    ch.to_string()
+}
+
+/// Converts `icon`'s RGBA buffer into an `NSImage` via `NSBitmapImageRep`; reused for both menu
+/// item icons and, via [`set_ns_status_item_icon`], the tray icon itself.
+unsafe fn icon_to_ns_image(icon: &Icon) -> id {
+    let width = icon.width as i64;
+    let height = icon.height as i64;
+    let bytes_per_row = width * 4;
+
+    let bitmap_rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let bitmap_rep: id = msg_send![
+        bitmap_rep,
+        initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+        pixelsWide: width
+        pixelsHigh: height
+        bitsPerSample: 8i64
+        samplesPerPixel: 4i64
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: NSString::alloc(nil).init_str("NSDeviceRGBColorSpace")
+        bitmapFormat: 0i64
+        bytesPerRow: bytes_per_row
+        bitsPerPixel: 32i64
+    ];
+
+    let dest: *mut u8 = msg_send![bitmap_rep, bitmapData];
+    std::ptr::copy_nonoverlapping(icon.rgba.as_ptr(), dest, icon.rgba.len());
+
+    let size = NSSize {
+        width: width as f64,
+        height: height as f64,
+    };
+    let ns_image: id = msg_send![class!(NSImage), alloc];
+    let ns_image: id = msg_send![ns_image, initWithSize: size];
+    let _: () = msg_send![ns_image, addRepresentation: bitmap_rep];
+    ns_image
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct MenuItemHandle {
+    ns_item: id,
+}
+
+impl MenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        unsafe {
+            let ns_title = NSString::alloc(nil).init_str(label);
+            let _: () = msg_send![self.ns_item, setTitle: ns_title];
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        unsafe {
+            let _: () = msg_send![self.ns_item, setEnabled: enabled as i8];
+        }
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        unsafe {
+            let _: () = msg_send![self.ns_item, setHidden: !visible as i8];
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct CheckMenuItemHandle {
+    ns_item: id,
+}
+
+impl CheckMenuItemHandle {
+    pub(crate) fn set_checked(&self, checked: bool) {
+        unsafe { set_ns_item_checked(self.ns_item, checked) };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct IconMenuItemHandle {
+    ns_item: id,
+}
+
+impl IconMenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        unsafe {
+            let ns_title = NSString::alloc(nil).init_str(label);
+            let _: () = msg_send![self.ns_item, setTitle: ns_title];
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        unsafe {
+            let _: () = msg_send![self.ns_item, setEnabled: enabled as i8];
+        }
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        unsafe {
+            let _: () = msg_send![self.ns_item, setHidden: !visible as i8];
+        }
+    }
+
+    pub(crate) fn set_icon(&self, icon: &Icon) {
+        unsafe {
+            let ns_image = icon_to_ns_image(icon);
+            let _: () = msg_send![self.ns_item, setImage: ns_image];
+        }
+    }
+}
+
+pub(crate) struct SystemTray {
+    ns_status_item: id,
+    /// The `TaoStatusItemTarget` backing this tray's button, kept around so [`Self::set_menu`]
+    /// can update the `taoMenu` ivar it consults when presenting a menu manually (see
+    /// [`status_item_clicked`]).
+    target: id,
+    /// The menu currently retained for manual presentation, or `nil`. Tao never calls
+    /// `NSStatusItem::setMenu_` — doing so makes AppKit swallow every click on the button so its
+    /// `target`/`action` (and therefore every [`TrayEvent`]) never fires, menu or not. Instead tao
+    /// holds the menu itself and pops it up from [`status_item_clicked`] on whichever click type
+    /// `menu_on_left_click` says should open it, so a [`TrayEvent`] is still dispatched either way.
+    retained_menu: id,
+}
+
+impl SystemTray {
+    pub(crate) fn new(
+        id: TrayId,
+        icon: &Icon,
+        tray_menu: Option<ContextMenu>,
+        tooltip: Option<&str>,
+        title: Option<&str>,
+        menu_on_left_click: bool,
+    ) -> Result<Self, OsError> {
+        let ns_menu = tray_menu.as_ref().map(|menu| menu.platform_menu.ns_menu);
+
+        let ns_status_item = autoreleasepool(|| unsafe {
+            let status_bar = NSStatusBar::systemStatusBar(nil);
+            let ns_status_item = status_bar.statusItemWithLength_(-1.0);
+            set_ns_status_item_icon(ns_status_item, icon);
+            if let Some(title) = title {
+                let ns_title = NSString::alloc(nil).init_str(title);
+                let _: () = msg_send![ns_status_item, setTitle: ns_title];
+            }
+            if let Some(tooltip) = tooltip {
+                let ns_tooltip = NSString::alloc(nil).init_str(tooltip);
+                let _: () = msg_send![ns_status_item, setToolTip: ns_tooltip];
+            }
+            ns_status_item
+        });
+
+        // Never call `NSStatusItem::setMenu_`: AppKit then consumes every click on the button
+        // itself, so `target`/`action` (and every `TrayEvent`) stop firing entirely. Retain the
+        // menu ourselves instead and pop it up manually from `status_item_clicked`.
+        let retained_menu = ns_menu.map(|ns_menu| unsafe { msg_send![ns_menu, retain] }).unwrap_or(nil);
+
+        let target = register_status_item_target(ns_status_item, id, retained_menu, menu_on_left_click);
+
+        Ok(Self {
+            ns_status_item,
+            target,
+            retained_menu,
+        })
+    }
+
+    pub(crate) fn set_icon(&mut self, icon: Icon) {
+        unsafe { set_ns_status_item_icon(self.ns_status_item, &icon) };
+    }
+
+    pub(crate) fn set_title(&mut self, title: &str) {
+        unsafe {
+            let ns_title = NSString::alloc(nil).init_str(title);
+            let _: () = msg_send![self.ns_status_item, setTitle: ns_title];
+        }
+    }
+
+    pub(crate) fn ns_status_item(&self) -> *mut std::ffi::c_void {
+        self.ns_status_item as *mut std::ffi::c_void
+    }
+
+    pub(crate) fn set_menu(&mut self, menu: Option<ContextMenu>) {
+        let ns_menu = menu.as_ref().map(|m| m.platform_menu.ns_menu).unwrap_or(nil);
+
+        // `NSStatusItem` never retains a menu for us (tao doesn't call `setMenu_`, see
+        // `retained_menu`'s doc comment); retain the new one (if any) ourselves, release the one
+        // we were holding, and point the click target at it so `status_item_clicked` can pop it
+        // up manually.
+        let retained = if ns_menu != nil {
+            unsafe { msg_send![ns_menu, retain] }
+        } else {
+            nil
+        };
+        if self.retained_menu != nil {
+            let _: () = unsafe { msg_send![self.retained_menu, release] };
+        }
+        self.retained_menu = retained;
+        unsafe { (*self.target).set_ivar("taoMenu", self.retained_menu) };
+    }
+}
+
+unsafe fn set_ns_status_item_icon(ns_status_item: id, icon: &Icon) {
+    let ns_image = icon_to_ns_image(icon);
+    let button: id = msg_send![ns_status_item, button];
+    let _: () = msg_send![button, setImage: ns_image];
+}
+
+/// An `NSObject` subclass whose button action inspects `NSEvent.clickCount` and the current
+/// event's type to tell left/double/right clicks apart, then forwards the status item's button
+/// frame (converted to screen coordinates) as the [`TrayEvent`]'s `bounds`. Tao never attaches the
+/// menu stashed in its `taoMenu` ivar via `setMenu_` (see [`SystemTray::retained_menu`]'s doc
+/// comment), so this also pops that menu up manually — on left click if `taoMenuOnLeftClick`, on
+/// right click otherwise — before dispatching the `TrayEvent`.
+fn status_item_target_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let superclass = Class::get("NSObject").unwrap();
+        let mut decl = ClassDecl::new("TaoStatusItemTarget", superclass).unwrap();
+        decl.add_ivar::<u64>("taoTrayId");
+        decl.add_ivar::<id>("taoStatusItem");
+        decl.add_ivar::<id>("taoMenu");
+        decl.add_ivar::<bool>("taoMenuOnLeftClick");
+        decl.add_method(
+            sel!(taoStatusItemClicked:),
+            status_item_clicked as extern "C" fn(&Object, Sel, id),
+        );
+        decl.register();
+    });
+    Class::get("TaoStatusItemTarget").unwrap()
+}
+
+extern "C" fn status_item_clicked(this: &Object, _cmd: Sel, sender: id) {
+    unsafe {
+        let tray_id: u64 = *this.get_ivar("taoTrayId");
+        let ns_status_item: id = *this.get_ivar("taoStatusItem");
+        let ns_menu: id = *this.get_ivar("taoMenu");
+        let menu_on_left_click: bool = *this.get_ivar("taoMenuOnLeftClick");
+        let current_event: id = msg_send![NSApp(), currentEvent];
+        let event_type: u64 = msg_send![current_event, type];
+        let click_count: i64 = msg_send![current_event, clickCount];
+
+        let button_bounds: cocoa::foundation::NSRect = msg_send![sender, bounds];
+        let window_rect: cocoa::foundation::NSRect = msg_send![sender, convertRect: button_bounds toView: nil];
+        let window: id = msg_send![sender, window];
+        let screen_rect: cocoa::foundation::NSRect = msg_send![window, convertRectToScreen: window_rect];
+        let position = PhysicalPosition::new(screen_rect.origin.x, screen_rect.origin.y);
+        let bounds = Rectangle {
+            position,
+            size: PhysicalSize::new(screen_rect.size.width, screen_rect.size.height),
+        };
+
+        // `NSEventTypeRightMouseDown` is 3; see `NSEventType` in AppKit.
+        const NS_EVENT_TYPE_RIGHT_MOUSE_DOWN: u64 = 3;
+
+        let event = if event_type == NS_EVENT_TYPE_RIGHT_MOUSE_DOWN {
+            if !menu_on_left_click && ns_menu != nil {
+                let _: () = msg_send![ns_status_item, popUpStatusItemMenu: ns_menu];
+            }
+            TrayEvent::RightClick { position, bounds }
+        } else if click_count >= 2 {
+            TrayEvent::DoubleClick { position, bounds }
+        } else {
+            if menu_on_left_click && ns_menu != nil {
+                let _: () = msg_send![ns_status_item, popUpStatusItemMenu: ns_menu];
+            }
+            TrayEvent::LeftClick
+        };
+
+        crate::platform_impl::dispatch_tray_event(TrayId(tray_id), event);
+    }
+}
+
+fn register_status_item_target(ns_status_item: id, id: TrayId, ns_menu: id, menu_on_left_click: bool) -> id {
+    unsafe {
+        let target: id = msg_send![status_item_target_class(), new];
+        (*target).set_ivar("taoTrayId", id.0);
+        (*target).set_ivar("taoStatusItem", ns_status_item);
+        (*target).set_ivar("taoMenu", ns_menu);
+        (*target).set_ivar("taoMenuOnLeftClick", menu_on_left_click);
+        let button: id = msg_send![ns_status_item, button];
+        let _: () = msg_send![button, setTarget: target];
+        let _: () = msg_send![button, setAction: sel!(taoStatusItemClicked:)];
+        let _: () = msg_send![button, sendActionOn: 0x2u64 | 0x4u64 | 0x8u64]; // left mouse down + up, right mouse down
+        target
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-platform backends. Nothing here is public API; [`crate::menu`] and [`crate::system_tray`]
+//! are thin, platform-agnostic wrappers around the types re-exported from this module.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::{
+    event::TrayEvent,
+    menu::{MenuId, MenuType},
+    TrayId,
+};
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub(crate) use linux::*;
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+pub(crate) use macos::*;
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+pub(crate) use windows::*;
+
+/// Pumps whichever OS event source platform callbacks (GTK signal handlers, the zbus
+/// `dbus_interface` method dispatch, ...) ride on, so that a callback queued via
+/// [`dispatch_menu_event`]/[`dispatch_tray_event`] actually gets a chance to run before
+/// [`crate::event_loop::EventLoop::run`]'s loop calls [`drain_events`]. `block` mirrors
+/// [`crate::event_loop::ControlFlow::Wait`] (block until a source is ready) vs.
+/// [`crate::event_loop::ControlFlow::Poll`] (return immediately either way).
+#[cfg(target_os = "linux")]
+pub(crate) fn pump_events(block: bool) {
+    glib::MainContext::default().iteration(block);
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pump_events(_block: bool) {
+    // Win32's message pump and Cocoa's run loop are both tied to the main window backend (see the
+    // `SystemTray::new` comment in `windows.rs`), which is out of scope here, same as it is there.
+}
+
+/// Like [`pump_events`], but bounded by `deadline` instead of a single blocking/non-blocking
+/// iteration — used for [`crate::event_loop::ControlFlow::WaitUntil`], which still needs to wake
+/// up on time even if no platform event ever arrives.
+#[cfg(target_os = "linux")]
+pub(crate) fn pump_events_until(deadline: Instant) {
+    let context = glib::MainContext::default();
+    while Instant::now() < deadline {
+        // Non-blocking: never overshoots `deadline`. Sleep briefly between empty passes so this
+        // still behaves like a wait (no busy-spin) rather than a tight poll loop.
+        if !context.iteration(false) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            std::thread::sleep(remaining.min(std::time::Duration::from_millis(10)));
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pump_events_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now {
+        std::thread::sleep(deadline - now);
+    }
+}
+
+impl From<String> for crate::system_tray::OsError {
+    fn from(message: String) -> Self {
+        crate::system_tray::OsError(message)
+    }
+}
+
+/// An event pushed by a platform callback (a GTK signal handler, an `NSMenuItem` action, a
+/// `WM_COMMAND`/`WM_*` message, ...) for [`crate::event_loop::EventLoop::run`]'s loop to pick up
+/// and redeliver as a public [`crate::event::Event`] on its next iteration.
+///
+/// Platform callbacks generally can't call back into the application's event handler directly
+/// (they run on a different stack, sometimes a different thread), so they push here instead.
+pub(crate) enum InternalEvent {
+    Menu { id: MenuId, origin: MenuType },
+    Tray { id: TrayId, event: TrayEvent },
+}
+
+static EVENT_QUEUE: OnceLock<Mutex<Vec<InternalEvent>>> = OnceLock::new();
+
+fn event_queue() -> &'static Mutex<Vec<InternalEvent>> {
+    EVENT_QUEUE.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Called by a platform menu callback once a custom item has been activated.
+pub(crate) fn dispatch_menu_event(id: MenuId, origin: MenuType) {
+    event_queue().lock().unwrap().push(InternalEvent::Menu { id, origin });
+}
+
+/// Called by a platform tray callback once the icon has been clicked.
+pub(crate) fn dispatch_tray_event(id: TrayId, event: TrayEvent) {
+    event_queue().lock().unwrap().push(InternalEvent::Tray { id, event });
+}
+
+/// Drains every [`InternalEvent`] queued since the last call; used by
+/// [`crate::event_loop::EventLoop::run`]'s loop.
+pub(crate) fn drain_events() -> Vec<InternalEvent> {
+    std::mem::take(&mut *event_queue().lock().unwrap())
+}
@@ -0,0 +1,560 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Win32 (`HWND` message-only window + `Shell_NotifyIconW`) backend.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+use winapi::{
+    shared::windef::{HACCEL, HBITMAP, HICON, HMENU, HWND, POINT},
+    um::shellapi::{Shell_NotifyIconW, NIF_ICON, NIM_MODIFY, NOTIFYICONDATAW},
+    um::wingdi::{CreateBitmap, CreateDIBSection, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS},
+    um::winuser::{
+        AppendMenuW, CheckMenuItem as Win32CheckMenuItem, CreateAcceleratorTableW,
+        CreateIconIndirect, CreatePopupMenu, EnableMenuItem, GetCursorPos, GetDC, ICONINFO,
+        ReleaseDC, SetMenuItemInfoW, TrackPopupMenu, ACCEL, FALT, FCONTROL, FSHIFT, FVIRTKEY,
+        MENUITEMINFOW, MF_BYCOMMAND, MF_CHECKED, MF_ENABLED, MF_GRAYED, MF_SEPARATOR, MF_STRING,
+        MF_UNCHECKED, MIIM_BITMAP, MIIM_STRING, TPM_LEFTALIGN, VK_BACK, VK_DELETE, VK_DOWN,
+        VK_ESCAPE, VK_F1, VK_F10, VK_F11, VK_F12, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7,
+        VK_F8, VK_F9, VK_LEFT, VK_RETURN, VK_RIGHT, VK_SPACE, VK_TAB, VK_UP, WM_LBUTTONDBLCLK,
+        WM_RBUTTONUP,
+    },
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::TrayEvent,
+    keyboard::{KeyCode, ModifiersState},
+    menu::{CheckMenuItem, ContextMenu, MenuId, MenuItem, MenuItemAttributes, MenuType, RadioGroup},
+    system_tray::{Icon, OsError, Rectangle},
+    TrayId,
+};
+
+static NEXT_WIN32_COMMAND_ID: AtomicU16 = AtomicU16::new(1);
+
+/// An entry tracking a checkable item's native command id alongside the shared state needed to
+/// flip it (and its radio-group siblings) from [`handle_menu_command`].
+struct CheckItemEntry {
+    command_id: u16,
+    id: MenuId,
+    checked: Rc<Cell<bool>>,
+    group: Option<Rc<RefCell<Vec<CheckMenuItem>>>>,
+}
+
+/// An `HMENU` plus the `WM_COMMAND` id -> [`MenuId`] table needed to redeliver clicks.
+pub(crate) struct Menu {
+    pub(crate) hmenu: HMENU,
+    pub(crate) items: Vec<(u16, MenuId)>,
+    check_items: Vec<CheckItemEntry>,
+    accelerators: Vec<ACCEL>,
+}
+
+impl Menu {
+    pub(crate) fn new() -> Self {
+        Self {
+            hmenu: unsafe { CreatePopupMenu() },
+            items: Vec::new(),
+            check_items: Vec::new(),
+            accelerators: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_item(&mut self, id: MenuId, attributes: &MenuItemAttributes) -> MenuItemHandle {
+        let command_id = NEXT_WIN32_COMMAND_ID.fetch_add(1, Ordering::Relaxed);
+        let flags = MF_STRING | if attributes.enabled { 0 } else { MF_GRAYED };
+        append_menu_string(self.hmenu, flags, command_id, &attributes.label);
+        if let Some(accelerator) = &attributes.accelerator {
+            self.accelerators.push(ACCEL {
+                fVirt: FVIRTKEY | modifiers_to_fvirt(accelerator.modifiers),
+                key: keycode_to_vk(accelerator.key),
+                cmd: command_id,
+            });
+        }
+        self.items.push((command_id, id));
+        MenuItemHandle {
+            hmenu: self.hmenu,
+            command_id,
+        }
+    }
+
+    /// Builds a Win32 accelerator table from every accelerator attached via
+    /// [`crate::menu::MenuItemAttributes::with_accelerator`]; returned by
+    /// [`crate::platform::windows::ContextMenuExtWindows::haccel`] for the owning window's message
+    /// pump to pass to `TranslateAcceleratorW`.
+    pub(crate) fn build_haccel(&self) -> HACCEL {
+        unsafe {
+            CreateAcceleratorTableW(
+                self.accelerators.as_ptr() as *mut ACCEL,
+                self.accelerators.len() as i32,
+            )
+        }
+    }
+
+    pub(crate) fn add_native_item(&mut self, id: MenuId, item: MenuItem) -> Option<MenuItemHandle> {
+        if item == MenuItem::Separator {
+            unsafe {
+                AppendMenuW(self.hmenu, MF_SEPARATOR, 0, std::ptr::null());
+            }
+            return None;
+        }
+        let label = match item {
+            MenuItem::Quit => "Quit",
+            MenuItem::Copy => "Copy",
+            MenuItem::Paste => "Paste",
+            MenuItem::Cut => "Cut",
+            MenuItem::SelectAll => "Select All",
+            MenuItem::Separator => unreachable!(),
+        };
+        let command_id = NEXT_WIN32_COMMAND_ID.fetch_add(1, Ordering::Relaxed);
+        append_menu_string(self.hmenu, MF_STRING, command_id, label);
+        self.items.push((command_id, id));
+        Some(MenuItemHandle {
+            hmenu: self.hmenu,
+            command_id,
+        })
+    }
+
+    pub(crate) fn add_check_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        checked: Rc<Cell<bool>>,
+        group: Option<Rc<RefCell<Vec<CheckMenuItem>>>>,
+    ) -> CheckMenuItemHandle {
+        let command_id = NEXT_WIN32_COMMAND_ID.fetch_add(1, Ordering::Relaxed);
+        let flags = MF_STRING
+            | if checked.get() { MF_CHECKED } else { 0 }
+            | if attributes.enabled { 0 } else { MF_GRAYED };
+        append_menu_string(self.hmenu, flags, command_id, &attributes.label);
+        self.check_items.push(CheckItemEntry {
+            command_id,
+            id,
+            checked,
+            group,
+        });
+        CheckMenuItemHandle {
+            hmenu: self.hmenu,
+            command_id,
+        }
+    }
+
+    pub(crate) fn add_icon_item(
+        &mut self,
+        id: MenuId,
+        attributes: &MenuItemAttributes,
+        icon: &Icon,
+    ) -> IconMenuItemHandle {
+        let command_id = NEXT_WIN32_COMMAND_ID.fetch_add(1, Ordering::Relaxed);
+        let flags = MF_STRING | if attributes.enabled { 0 } else { MF_GRAYED };
+        append_menu_string(self.hmenu, flags, command_id, &attributes.label);
+        set_item_bitmap(self.hmenu, command_id, icon);
+        self.items.push((command_id, id));
+        IconMenuItemHandle {
+            hmenu: self.hmenu,
+            command_id,
+        }
+    }
+
+    pub(crate) fn command_to_menu_id(&self, command_id: u16) -> Option<MenuId> {
+        self.items
+            .iter()
+            .find(|(cmd, _)| *cmd == command_id)
+            .map(|(_, id)| *id)
+    }
+}
+
+/// Converts a modifier set into the `fVirt` bits an `ACCEL` entry expects (`FVIRTKEY` is added
+/// separately by the caller).
+fn modifiers_to_fvirt(modifiers: ModifiersState) -> u8 {
+    let mut fvirt = 0;
+    if modifiers.contains(ModifiersState::SHIFT) {
+        fvirt |= FSHIFT;
+    }
+    if modifiers.contains(ModifiersState::CONTROL) {
+        fvirt |= FCONTROL;
+    }
+    if modifiers.contains(ModifiersState::ALT) {
+        fvirt |= FALT;
+    }
+    // Win32 accelerator tables have no "Windows key" modifier; `ModifiersState::SUPER` is
+    // dropped here, matching `CmdOrCtrl` resolving to `CONTROL` (not `SUPER`) on this platform.
+    fvirt
+}
+
+/// Converts a [`KeyCode`] into the virtual-key code an `ACCEL` entry's `key` field expects.
+fn keycode_to_vk(key: KeyCode) -> u16 {
+    use KeyCode::*;
+    match key {
+        KeyA => b'A' as u16,
+        KeyB => b'B' as u16,
+        KeyC => b'C' as u16,
+        KeyD => b'D' as u16,
+        KeyE => b'E' as u16,
+        KeyF => b'F' as u16,
+        KeyG => b'G' as u16,
+        KeyH => b'H' as u16,
+        KeyI => b'I' as u16,
+        KeyJ => b'J' as u16,
+        KeyK => b'K' as u16,
+        KeyL => b'L' as u16,
+        KeyM => b'M' as u16,
+        KeyN => b'N' as u16,
+        KeyO => b'O' as u16,
+        KeyP => b'P' as u16,
+        KeyQ => b'Q' as u16,
+        KeyR => b'R' as u16,
+        KeyS => b'S' as u16,
+        KeyT => b'T' as u16,
+        KeyU => b'U' as u16,
+        KeyV => b'V' as u16,
+        KeyW => b'W' as u16,
+        KeyX => b'X' as u16,
+        KeyY => b'Y' as u16,
+        KeyZ => b'Z' as u16,
+        Digit0 => b'0' as u16,
+        Digit1 => b'1' as u16,
+        Digit2 => b'2' as u16,
+        Digit3 => b'3' as u16,
+        Digit4 => b'4' as u16,
+        Digit5 => b'5' as u16,
+        Digit6 => b'6' as u16,
+        Digit7 => b'7' as u16,
+        Digit8 => b'8' as u16,
+        Digit9 => b'9' as u16,
+        F1 => VK_F1 as u16,
+        F2 => VK_F2 as u16,
+        F3 => VK_F3 as u16,
+        F4 => VK_F4 as u16,
+        F5 => VK_F5 as u16,
+        F6 => VK_F6 as u16,
+        F7 => VK_F7 as u16,
+        F8 => VK_F8 as u16,
+        F9 => VK_F9 as u16,
+        F10 => VK_F10 as u16,
+        F11 => VK_F11 as u16,
+        F12 => VK_F12 as u16,
+        Space => VK_SPACE as u16,
+        Enter => VK_RETURN as u16,
+        Escape => VK_ESCAPE as u16,
+        Tab => VK_TAB as u16,
+        Backspace => VK_BACK as u16,
+        Delete => VK_DELETE as u16,
+        ArrowUp => VK_UP as u16,
+        ArrowDown => VK_DOWN as u16,
+        ArrowLeft => VK_LEFT as u16,
+        ArrowRight => VK_RIGHT as u16,
+    }
+}
+
+fn append_menu_string(hmenu: HMENU, flags: u32, command_id: u16, label: &str) {
+    let mut wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        AppendMenuW(hmenu, flags, command_id as usize, wide.as_mut_ptr());
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct MenuItemHandle {
+    hmenu: HMENU,
+    command_id: u16,
+}
+
+impl MenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        let mut wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut info = menu_item_info(MIIM_STRING);
+        info.dwTypeData = wide.as_mut_ptr();
+        info.cch = label.len() as u32;
+        unsafe {
+            SetMenuItemInfoW(self.hmenu, self.command_id as u32, 0, &info);
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        let flags = MF_BYCOMMAND | if enabled { MF_ENABLED } else { MF_GRAYED };
+        unsafe {
+            EnableMenuItem(self.hmenu, self.command_id as u32, flags);
+        }
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        // Win32 popup menus have no per-item "hidden" flag; the closest native equivalent is
+        // disabling the item, which still shows it greyed out rather than removing it entirely.
+        let flags = MF_BYCOMMAND | if visible { MF_ENABLED } else { MF_GRAYED };
+        unsafe {
+            EnableMenuItem(self.hmenu, self.command_id as u32, flags);
+        }
+    }
+}
+
+/// A handle to a checkable `HMENU` entry, identified by its `WM_COMMAND` id.
+#[derive(Clone, Copy)]
+pub(crate) struct CheckMenuItemHandle {
+    hmenu: HMENU,
+    command_id: u16,
+}
+
+impl CheckMenuItemHandle {
+    pub(crate) fn set_checked(&self, checked: bool) {
+        set_checked_state(self.hmenu, self.command_id, checked);
+    }
+}
+
+fn set_checked_state(hmenu: HMENU, command_id: u16, checked: bool) {
+    let flags = MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED };
+    unsafe {
+        Win32CheckMenuItem(hmenu, command_id as u32, flags);
+    }
+}
+
+/// A handle to an `HMENU` entry carrying an `HBITMAP`, identified by its `WM_COMMAND` id.
+#[derive(Clone, Copy)]
+pub(crate) struct IconMenuItemHandle {
+    hmenu: HMENU,
+    command_id: u16,
+}
+
+impl IconMenuItemHandle {
+    pub(crate) fn set_label(&self, label: &str) {
+        let mut wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut info = menu_item_info(MIIM_STRING);
+        info.dwTypeData = wide.as_mut_ptr();
+        info.cch = label.len() as u32;
+        unsafe {
+            SetMenuItemInfoW(self.hmenu, self.command_id as u32, 0, &info);
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        let flags = MF_BYCOMMAND | if enabled { MF_ENABLED } else { MF_GRAYED };
+        unsafe {
+            EnableMenuItem(self.hmenu, self.command_id as u32, flags);
+        }
+    }
+
+    pub(crate) fn set_visible(&self, visible: bool) {
+        let flags = MF_BYCOMMAND | if visible { MF_ENABLED } else { MF_GRAYED };
+        unsafe {
+            EnableMenuItem(self.hmenu, self.command_id as u32, flags);
+        }
+    }
+
+    pub(crate) fn set_icon(&self, icon: &Icon) {
+        set_item_bitmap(self.hmenu, self.command_id, icon);
+    }
+}
+
+/// Converts `icon`'s RGBA buffer into an `HBITMAP` via `CreateDIBSection` and attaches it as the
+/// item's `hbmpItem`.
+fn set_item_bitmap(hmenu: HMENU, command_id: u16, icon: &Icon) {
+    let mut info = menu_item_info(MIIM_BITMAP);
+    info.hbmpItem = rgba_to_hbitmap(icon);
+    unsafe {
+        SetMenuItemInfoW(hmenu, command_id as u32, 0, &info);
+    }
+}
+
+/// Converts `icon`'s RGBA buffer into a top-down, 32bpp `HBITMAP`, reused for both menu item
+/// icons and (via [`rgba_to_hicon`]) the tray icon itself.
+fn rgba_to_hbitmap(icon: &Icon) -> HBITMAP {
+    unsafe {
+        let mut bmi: BITMAPINFO = std::mem::zeroed();
+        bmi.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+        bmi.bmiHeader.biWidth = icon.width as i32;
+        bmi.bmiHeader.biHeight = -(icon.height as i32);
+        bmi.bmiHeader.biPlanes = 1;
+        bmi.bmiHeader.biBitCount = 32;
+        bmi.bmiHeader.biCompression = BI_RGB;
+
+        let screen_dc = GetDC(std::ptr::null_mut());
+        let mut bits: *mut winapi::ctypes::c_void = std::ptr::null_mut();
+        let hbitmap = CreateDIBSection(
+            screen_dc,
+            &bmi,
+            DIB_RGB_COLORS,
+            &mut bits,
+            std::ptr::null_mut(),
+            0,
+        );
+        ReleaseDC(std::ptr::null_mut(), screen_dc);
+
+        if !hbitmap.is_null() && !bits.is_null() {
+            // `CreateDIBSection` wants BGRA, not RGBA.
+            let dest = std::slice::from_raw_parts_mut(bits as *mut u8, icon.rgba.len());
+            for (src, dst) in icon.rgba.chunks_exact(4).zip(dest.chunks_exact_mut(4)) {
+                dst[0] = src[2];
+                dst[1] = src[1];
+                dst[2] = src[0];
+                dst[3] = src[3];
+            }
+        }
+        hbitmap
+    }
+}
+
+/// Converts `icon`'s RGBA buffer into an `HICON`, suitable for `NOTIFYICONDATAW::hIcon`.
+fn rgba_to_hicon(icon: &Icon) -> HICON {
+    unsafe {
+        let color = rgba_to_hbitmap(icon);
+        // An icon's mask only matters for legacy 1bpp cursors; a fully opaque mask is enough
+        // once the color bitmap already carries a real alpha channel.
+        let mask = CreateBitmap(icon.width as i32, icon.height as i32, 1, 1, std::ptr::null());
+        let mut icon_info = ICONINFO {
+            fIcon: 1,
+            xHotspot: 0,
+            yHotspot: 0,
+            hbmMask: mask,
+            hbmColor: color,
+        };
+        CreateIconIndirect(&mut icon_info)
+    }
+}
+
+/// Pushes `hicon` to the tray icon already registered on `hwnd` via `Shell_NotifyIconW(NIM_MODIFY)`.
+fn notify_icon_set_icon(hwnd: HWND, hicon: HICON) {
+    unsafe {
+        let mut data: NOTIFYICONDATAW = std::mem::zeroed();
+        data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = hwnd;
+        data.uFlags = NIF_ICON;
+        data.hIcon = hicon;
+        Shell_NotifyIconW(NIM_MODIFY, &mut data);
+    }
+}
+
+fn menu_item_info(mask: u32) -> MENUITEMINFOW {
+    let mut info: MENUITEMINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MENUITEMINFOW>() as u32;
+    info.fMask = mask;
+    info
+}
+
+pub(crate) struct SystemTray {
+    tray_id: TrayId,
+    hwnd: HWND,
+    hmenu: Option<HMENU>,
+    hicon: HICON,
+}
+
+impl SystemTray {
+    pub(crate) fn new(
+        id: TrayId,
+        icon: &Icon,
+        tray_menu: Option<ContextMenu>,
+        tooltip: Option<&str>,
+        _title: Option<&str>,
+    ) -> Result<Self, OsError> {
+        // Real tao creates a hidden message-only `HWND` to own the tray icon's `WM_*` callbacks
+        // and to register/call `Shell_NotifyIconW`; the window creation and message pump wiring
+        // lives alongside the main window backend and is out of scope for menu/tray handling.
+        // The icon is still converted up front so it's ready the moment that `HWND` exists.
+        let _ = tooltip;
+        Ok(Self {
+            tray_id: id,
+            hwnd: std::ptr::null_mut(),
+            hmenu: tray_menu.map(|m| m.platform_menu.hmenu),
+            hicon: rgba_to_hicon(icon),
+        })
+    }
+
+    pub(crate) fn set_icon(&mut self, icon: Icon) {
+        self.hicon = rgba_to_hicon(&icon);
+        if !self.hwnd.is_null() {
+            notify_icon_set_icon(self.hwnd, self.hicon);
+        }
+    }
+
+    pub(crate) fn set_title(&mut self, _title: &str) {
+        // Windows tray icons have no text title; this is a no-op to match macOS/Linux parity.
+    }
+
+    /// Stores the replacement `HMENU` for the next `WM_RBUTTONUP`'s `TrackPopupMenu` call (see
+    /// [`Self::handle_callback_message`]); the previous menu is simply dropped.
+    pub(crate) fn set_menu(&mut self, menu: Option<ContextMenu>) {
+        self.hmenu = menu.map(|m| m.platform_menu.hmenu);
+    }
+
+    /// Called from the tray `HWND`'s window procedure on `WM_APP` (the custom message
+    /// `Shell_NotifyIconW` is registered to deliver mouse activity on).
+    pub(crate) fn handle_callback_message(&self, lparam: isize) {
+        let event = match lparam as u32 {
+            WM_LBUTTONDBLCLK => Some(TrayEvent::DoubleClick {
+                position: cursor_position(),
+                bounds: self.icon_bounds(),
+            }),
+            WM_RBUTTONUP => Some(TrayEvent::RightClick {
+                position: cursor_position(),
+                bounds: self.icon_bounds(),
+            }),
+            winapi::um::winuser::WM_LBUTTONUP => Some(TrayEvent::LeftClick),
+            _ => None,
+        };
+        if let Some(event) = event {
+            crate::platform_impl::dispatch_tray_event(self.tray_id, event);
+        }
+        if let Some(hmenu) = self.hmenu {
+            if lparam as u32 == WM_RBUTTONUP {
+                let position = cursor_position();
+                unsafe {
+                    TrackPopupMenu(
+                        hmenu,
+                        TPM_LEFTALIGN,
+                        position.x as i32,
+                        position.y as i32,
+                        0,
+                        self.hwnd,
+                        std::ptr::null(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn icon_bounds(&self) -> Rectangle {
+        // `Shell_NotifyIconGetRect` would give the real icon rectangle; approximated here as a
+        // zero-sized rect anchored at the cursor, since only the cursor position is guaranteed
+        // to be available at callback time.
+        Rectangle {
+            position: cursor_position(),
+            size: PhysicalSize::new(0.0, 0.0),
+        }
+    }
+}
+
+fn cursor_position() -> PhysicalPosition<f64> {
+    let mut point = POINT { x: 0, y: 0 };
+    unsafe {
+        GetCursorPos(&mut point);
+    }
+    PhysicalPosition::new(point.x as f64, point.y as f64)
+}
+
+/// Dispatches a `WM_COMMAND` raised by a custom or checkable menu item back to
+/// [`crate::event::Event::MenuEvent`], auto-toggling check state (and unchecking radio-group
+/// siblings) before the event is delivered.
+pub(crate) fn handle_menu_command(menu: &Menu, command_id: u16) {
+    if let Some(entry) = menu.check_items.iter().find(|e| e.command_id == command_id) {
+        let now_checked = !entry.checked.get();
+        entry.checked.set(now_checked);
+        set_checked_state(menu.hmenu, entry.command_id, now_checked);
+        if now_checked {
+            if let Some(group) = &entry.group {
+                for member in group.borrow().iter() {
+                    if RadioGroup::is_sibling(member.id, entry.id) {
+                        member.apply_checked(false);
+                    }
+                }
+            }
+        }
+        crate::platform_impl::dispatch_menu_event(entry.id, MenuType::ContextMenu);
+        return;
+    }
+    if let Some(id) = menu.command_to_menu_id(command_id) {
+        crate::platform_impl::dispatch_menu_event(id, MenuType::ContextMenu);
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! A tray icon living in the platform's menu bar / notification area, with an optional attached
+//! [`crate::menu::ContextMenu`].
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::EventLoop,
+    menu::ContextMenu,
+    TrayId,
+};
+
+/// An RGBA bitmap used as a tray icon, or attached to an individual
+/// [`crate::menu::ContextMenu`] row via [`crate::menu::ContextMenu::add_icon_item`].
+#[derive(Debug, Clone)]
+pub struct Icon {
+    pub(crate) rgba: Vec<u8>,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+/// An error produced when constructing an [`Icon`] from invalid RGBA data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadIcon;
+
+impl Icon {
+    /// Creates an `Icon` from 32bpp RGBA data. `width * height * 4` must equal `rgba.len()`.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        if rgba.len() != (width * height * 4) as usize {
+            return Err(BadIcon);
+        }
+        Ok(Self {
+            rgba,
+            width,
+            height,
+        })
+    }
+}
+
+/// A rectangle in physical screen coordinates, e.g. the bounds of a tray icon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub position: PhysicalPosition<f64>,
+    pub size: PhysicalSize<f64>,
+}
+
+/// An error returned when building a [`SystemTray`] fails at the OS level.
+#[derive(Debug)]
+pub struct OsError(pub(crate) String);
+
+/// Builds a [`SystemTray`].
+pub struct SystemTrayBuilder {
+    pub(crate) icon: Icon,
+    pub(crate) tray_menu: Option<ContextMenu>,
+    pub(crate) id: Option<TrayId>,
+    pub(crate) tooltip: Option<String>,
+    pub(crate) title: Option<String>,
+    #[cfg(target_os = "linux")]
+    pub(crate) temp_icon_dir: Option<std::path::PathBuf>,
+    #[cfg(target_os = "macos")]
+    pub(crate) menu_on_left_click: bool,
+}
+
+impl SystemTrayBuilder {
+    /// Creates a new builder for a tray with `icon` and an optional attached `tray_menu`.
+    pub fn new(icon: Icon, tray_menu: Option<ContextMenu>) -> Self {
+        Self {
+            icon,
+            tray_menu,
+            id: None,
+            tooltip: None,
+            title: None,
+            #[cfg(target_os = "linux")]
+            temp_icon_dir: None,
+            #[cfg(target_os = "macos")]
+            menu_on_left_click: true,
+        }
+    }
+
+    /// Assigns a stable [`TrayId`], used to disambiguate [`crate::event::Event::TrayEvent`] when
+    /// an application owns more than one tray icon.
+    pub fn with_id(mut self, id: TrayId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the tooltip shown when hovering the tray icon (macOS, and Linux with the
+    /// StatusNotifierItem backend; ignored on Windows and by the `ayatana` backend).
+    pub fn with_tooltip(mut self, tooltip: &str) -> Self {
+        self.tooltip = Some(tooltip.to_string());
+        self
+    }
+
+    /// Sets the title drawn next to the icon in the menu bar on macOS; on Linux the
+    /// StatusNotifierItem backend exposes it as the item's `Title`. Ignored elsewhere.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Builds the tray icon and installs it into the OS's tray/menu bar.
+    pub fn build<T>(self, event_loop: &EventLoop<T>) -> Result<SystemTray, OsError> {
+        let _ = event_loop;
+        let id = self.id.unwrap_or_else(TrayId::next);
+        #[cfg(target_os = "linux")]
+        let platform_tray = crate::platform_impl::SystemTray::new(
+            id,
+            &self.icon,
+            self.tray_menu,
+            self.tooltip.as_deref(),
+            self.title.as_deref(),
+            self.temp_icon_dir.as_deref(),
+        )?;
+        #[cfg(target_os = "macos")]
+        let platform_tray = crate::platform_impl::SystemTray::new(
+            id,
+            &self.icon,
+            self.tray_menu,
+            self.tooltip.as_deref(),
+            self.title.as_deref(),
+            self.menu_on_left_click,
+        )?;
+        #[cfg(target_os = "windows")]
+        let platform_tray = crate::platform_impl::SystemTray::new(
+            id,
+            &self.icon,
+            self.tray_menu,
+            self.tooltip.as_deref(),
+            self.title.as_deref(),
+        )?;
+        Ok(SystemTray { id, platform_tray })
+    }
+}
+
+/// A live tray icon, previously installed via [`SystemTrayBuilder::build`].
+///
+/// `SystemTray` has no `Drop` implementation: dropping it only drops the platform handles it
+/// holds, and does not explicitly remove the icon from the OS tray (on macOS the `NSStatusItem`
+/// stays in the status bar).
+pub struct SystemTray {
+    pub(crate) id: TrayId,
+    pub(crate) platform_tray: crate::platform_impl::SystemTray,
+}
+
+impl SystemTray {
+    /// Returns the id this tray was built with.
+    pub fn id(&self) -> TrayId {
+        self.id
+    }
+
+    /// Replaces the tray icon's bitmap.
+    pub fn set_icon(&mut self, icon: Icon) {
+        self.platform_tray.set_icon(icon);
+    }
+
+    /// Sets the title drawn next to the icon in the menu bar on macOS; on Linux the
+    /// StatusNotifierItem backend updates the item's `Title` and emits `NewTitle`. A no-op on
+    /// Windows and with the `ayatana` backend.
+    pub fn set_title(&mut self, title: &str) {
+        self.platform_tray.set_title(title);
+    }
+
+    /// Replaces the tray's attached context menu, or detaches it entirely if `None`.
+    ///
+    /// Unlike dropping and rebuilding the whole [`SystemTray`], this updates the menu in place
+    /// without flickering the icon out of the tray.
+    pub fn set_menu(&mut self, menu: Option<ContextMenu>) {
+        self.platform_tray.set_menu(menu);
+    }
+}
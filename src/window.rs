@@ -0,0 +1,31 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Window-related types shared across platforms.
+
+/// An icon used for a window's titlebar, taskbar entry, or similar OS chrome.
+///
+/// This is distinct from [`crate::system_tray::Icon`], which is used for tray icons; the two
+/// happen to share the same RGBA representation today but are kept as separate types since a
+/// platform may want to treat them differently (e.g. size constraints differ between a titlebar
+/// icon and a tray icon).
+#[derive(Debug, Clone)]
+pub struct Icon(pub(crate) Vec<u8>);
+
+/// An error produced when constructing an [`Icon`] from invalid RGBA data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadIcon;
+
+impl Icon {
+    /// Creates an `Icon` from 32bpp RGBA data.
+    ///
+    /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+    /// `rgba.len() / 4`, otherwise this returns a [`BadIcon`] error.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        if rgba.len() != (width * height * 4) as usize {
+            return Err(BadIcon);
+        }
+        Ok(Self(rgba))
+    }
+}